@@ -1,15 +1,54 @@
 from .johnnycanencrypt import (
     Johnny,
+    Cert,
+    DecryptionResult,
+    EncryptionStream,
+    DecryptionStream,
+    VerificationResult,
     create_newkey,
     encrypt_bytes_to_file,
     encrypt_bytes_to_bytes,
     encrypt_file_internal,
+    encrypt_and_sign_bytes,
+    encrypt_and_sign_file,
+    encrypt_bytes_with_password,
+    encrypt_file_with_password,
+    decrypt_bytes_with_password,
+    decrypt_file_with_password,
+    decrypt_bytes_with_keys,
+    decrypt_file_with_keys,
+    encrypt_stream,
+    decrypt_stream,
     parse_cert_file,
+    verify_bytes_detached,
+    verify_file_detached,
+    change_password,
+    revoke_cert,
+    apply_revocation,
+    add_userid,
+    revoke_userid,
+    set_primary_userid,
+    add_subkey,
+    set_subkey_expiration,
+    revoke_subkey,
+    set_expiration,
+)
+from .exceptions import (
+    KeyNotFoundError,
+    CryptoError,
+    WrongPasswordError,
+    NoDecryptionKeyError,
+    MalformedMessageError,
+    KeyParseError,
+    NoSigningKeyError,
+    PolicyRejectedError,
+    CancelledError,
 )
-from .exceptions import KeyNotFoundError
 
 import os
 import shutil
+import stat
+import tempfile
 
 
 def _delete_key_file(filepath):
@@ -20,6 +59,36 @@ def _delete_key_file(filepath):
         pass
 
 
+def _file_mode(filepath):
+    """Returns the mode of the existing file, or the default mode for a new file"""
+    try:
+        return stat.S_IMODE(os.stat(filepath).st_mode)
+    except FileNotFoundError:
+        umask = os.umask(0)
+        os.umask(umask)
+        return 0o666 & ~umask
+
+
+def _write_file(filepath, text, secret=False):
+    """Writes the text to a temporary file next to filepath, and then renames it
+    over filepath, so that a failure never leaves a partially written file.
+
+    The secret keys stay only readable by the owner, the other files keep the mode
+    of the existing file, or get the default mode from the umask."""
+    dirname, basename = os.path.split(os.path.abspath(filepath))
+    fd, tmppath = tempfile.mkstemp(prefix=f".{basename}.", suffix=".tmp", dir=dirname)
+    try:
+        with os.fdopen(fd, "w") as fobj:
+            fobj.write(text)
+        # mkstemp creates the file with 0600
+        if not secret:
+            os.chmod(tmppath, _file_mode(filepath))
+        os.replace(tmppath, filepath)
+    except BaseException:
+        _delete_key_file(tmppath)
+        raise
+
+
 class Key:
     "Returns a Key object."
 
@@ -56,9 +125,9 @@ class KeyStore:
             if fullpath[-4:] in [".asc", ".pub", ".sec"]:
                 try:
                     uids, fingerprint, keytype = parse_cert_file(fullpath)
-                except Exception as e:
-                    # TODO: Handle parsing error here
-                    pass
+                except KeyParseError:
+                    # Skip any file which is not a valid key
+                    continue
                 self.add_key_to_cache(fullpath, uids, fingerprint, keytype)
 
     def add_key_to_cache(self, fullpath, uids, fingerprint, keytype):
@@ -85,10 +154,13 @@ class KeyStore:
                 if uid_keyname in uid and uid[uid_keyname]:
                     value = uid[uid_keyname]
                     keys = cache.get(value, {"public": [], "secret": []})
+                    # The same key can be imported again after an update
                     if not keytype:
-                        keys["public"].append(key)
+                        if key not in keys["public"]:
+                            keys["public"].append(key)
                     else:
-                        keys["secret"].append(key)
+                        if key not in keys["secret"]:
+                            keys["secret"].append(key)
                     # Now set the values cache
                     cache[value] = keys
 
@@ -192,19 +264,44 @@ class KeyStore:
             return keys["secret"]
 
     def create_newkey(
-        self, password: str, uid: str = "", ciphersuite: str = "RSA4k"
+        self,
+        password: str,
+        uid="",
+        ciphersuite: str = "RSA4k",
+        creation_time=None,
+        expiration=None,
+        subkeys_expiration=None,
+        signing=True,
+        encryption="storage",
+        authentication=False,
     ) -> Key:
-        """Returns a public `Key` object after creating a new key in the store
-
-        :param password: The password for the key as str.
-        :param uid: The text for the uid value as str.
-        :param ciphersuite: Default RSA4k, other values are RSA2k, Cv25519
+        """Returns a public `Key` object after creating a new key in the store. The revocation
+        certificate of the key is saved as fingerprint.rev in the store directory.
+
+        :param password: The password for the key as str, None for no password.
+        :param uid: The text for the uid value as str, or a list of uids.
+        :param ciphersuite: Default RSA4k, other values are RSA2k, RSA3k, Cv25519, P256, P384, P521
+        :param creation_time: Creation time as seconds since epoch, default is now.
+        :param expiration: Validity of the primary key in seconds, default never expires.
+        :param subkeys_expiration: Validity of the subkeys in seconds, default same as the primary key.
+        :param signing: Default True, creates a signing subkey.
+        :param encryption: Default storage, other values are transport, both or none.
+        :param authentication: Default False, creates an authentication subkey if True.
         """
-        public, secret, fingerprint = create_newkey(password, uid, ciphersuite)
+        public, secret, fingerprint, revocation = create_newkey(
+            password,
+            uid,
+            ciphersuite,
+            creation_time=creation_time,
+            expiration=expiration,
+            subkeys_expiration=subkeys_expiration,
+            signing=signing,
+            encryption=encryption,
+            authentication=authentication,
+        )
         # Now save the public key
         key_filename = os.path.join(self.path, f"{fingerprint}.pub")
-        with open(key_filename, "w") as fobj:
-            fobj.write(public)
+        _write_file(key_filename, public)
 
         self.import_cert(key_filename, onplace=True)
 
@@ -212,13 +309,184 @@ class KeyStore:
 
         # Now save the secret key
         key_filename = os.path.join(self.path, f"{fingerprint}.sec")
-        with open(key_filename, "w") as fobj:
-            fobj.write(secret)
+        _write_file(key_filename, secret, secret=True)
 
         self.import_cert(key_filename, onplace=True)
 
+        # Now save the revocation certificate, the store does not load .rev files
+        _write_file(os.path.join(self.path, f"{fingerprint}.rev"), revocation)
+
         return key
 
+    def _update_key(self, fingerprint, public, secret) -> Key:
+        "Writes the updated public and secret key in the store, returns the public `Key`"
+        keys = self.fingerprints_cache[fingerprint]
+        for keytype, value, extension in [
+            ("public", public, ".pub"),
+            ("secret", secret, ".sec"),
+        ]:
+            if keys[keytype]:
+                key_filename = keys[keytype].keypath
+            else:
+                key_filename = os.path.join(self.path, f"{fingerprint}{extension}")
+            _write_file(key_filename, value, secret=keytype == "secret")
+            self.import_cert(key_filename, onplace=True)
+        return self.get_key(fingerprint)
+
+    def _get_secret_key(self, key) -> Key:
+        "Returns the secret `Key` for the given fingerprint or Key object"
+        if type(key) == str:  # Means we have a fingerprint
+            return self.get_key(key, keytype="secret")
+        return self.get_key(key.fingerprint, keytype="secret")
+
+    def add_userid(self, key, uid, password) -> Key:
+        """Adds a new user ID to the key in the store, returns the public `Key`.
+
+        :param key: Fingerprint or Key object
+        :param uid: The new user ID as str
+        :param password: Password of the secret key
+        """
+        k = self._get_secret_key(key)
+        public, secret = add_userid(k.keypath, password, uid)
+        return self._update_key(k.fingerprint, public, secret)
+
+    def revoke_userid(
+        self, key, uid, password, reason="unspecified", message=""
+    ) -> Key:
+        """Revokes the user ID of the key in the store, returns the public `Key`.
+
+        :param key: Fingerprint or Key object
+        :param uid: The user ID to revoke as str
+        :param password: Password of the secret key
+        :param reason: Either unspecified or retired
+        :param message: Human readable reason for the revocation
+        """
+        k = self._get_secret_key(key)
+        public, secret = revoke_userid(k.keypath, password, uid, reason, message)
+        return self._update_key(k.fingerprint, public, secret)
+
+    def set_primary_userid(self, key, uid, password) -> Key:
+        """Marks the user ID as the primary user ID of the key in the store, returns the public `Key`.
+
+        :param key: Fingerprint or Key object
+        :param uid: The user ID as str
+        :param password: Password of the secret key
+        """
+        k = self._get_secret_key(key)
+        public, secret = set_primary_userid(k.keypath, password, uid)
+        return self._update_key(k.fingerprint, public, secret)
+
+    def add_subkey(
+        self,
+        key,
+        password,
+        ciphersuite="RSA4k",
+        expiration=None,
+        signing=False,
+        encryption=None,
+        authentication=False,
+    ) -> Key:
+        """Adds a new subkey to the key in the store, returns the public `Key`.
+
+        :param key: Fingerprint or Key object
+        :param password: Password of the secret key
+        :param ciphersuite: Default RSA4k, other values are RSA2k, RSA3k, Cv25519, P256, P384, P521
+        :param expiration: Validity of the subkey in seconds, default never expires.
+        :param signing: Default False, if True the subkey can sign.
+        :param encryption: storage, transport, both or none, default is storage unless signing or authentication.
+        :param authentication: Default False, if True the subkey can authenticate.
+        """
+        k = self._get_secret_key(key)
+        public, secret = add_subkey(
+            k.keypath,
+            password,
+            ciphersuite,
+            expiration=expiration,
+            signing=signing,
+            encryption=encryption,
+            authentication=authentication,
+        )
+        return self._update_key(k.fingerprint, public, secret)
+
+    def set_subkey_expiration(self, key, fingerprint, password, when) -> Key:
+        """Sets the expiration time of the subkey, returns the public `Key`.
+
+        :param key: Fingerprint or Key object
+        :param fingerprint: Fingerprint of the subkey
+        :param password: Password of the secret key
+        :param when: Expiration time in seconds since epoch, None for never.
+        """
+        k = self._get_secret_key(key)
+        public, secret = set_subkey_expiration(k.keypath, password, fingerprint, when)
+        return self._update_key(k.fingerprint, public, secret)
+
+    def revoke_subkey(
+        self, key, fingerprint, password, reason="unspecified", message=""
+    ) -> Key:
+        """Revokes the subkey, returns the public `Key`.
+
+        :param key: Fingerprint or Key object
+        :param fingerprint: Fingerprint of the subkey
+        :param password: Password of the secret key
+        :param reason: Either unspecified, compromised, superseded or retired
+        :param message: Human readable reason for the revocation
+        """
+        k = self._get_secret_key(key)
+        public, secret = revoke_subkey(
+            k.keypath, password, fingerprint, reason, message
+        )
+        return self._update_key(k.fingerprint, public, secret)
+
+    def set_expiration(self, key, password, when, subkeys=False) -> Key:
+        """Sets the expiration time of the key in the store, returns the public `Key`.
+
+        :param key: Fingerprint or Key object
+        :param password: Password of the secret key
+        :param when: Expiration time in seconds since epoch, None for never.
+        :param subkeys: Default False, if True sets the same expiration time for all the subkeys.
+        """
+        k = self._get_secret_key(key)
+        public, secret = set_expiration(k.keypath, password, when, subkeys)
+        return self._update_key(k.fingerprint, public, secret)
+
+    def change_password(self, key, password, newpassword) -> Key:
+        """Changes the password of the secret key in the store, returns the secret `Key`.
+
+        :param key: Fingerprint or secret Key object
+        :param password: The current password of the secret key
+        :param newpassword: The new password, None to remove the password
+        """
+        k = self._get_secret_key(key)
+        secret = change_password(k.keypath, password, newpassword)
+        _write_file(k.keypath, secret, secret=True)
+        return k
+
+    def apply_revocation(self, key, revocation) -> Key:
+        """Merges the revocation certificate into the key(s) in the store, returns the public `Key`.
+
+        :param key: Fingerprint or Key object
+        :param revocation: The revocation certificate as str or bytes
+        """
+        if type(key) == str:  # Means we have a fingerprint
+            fingerprint = key
+        else:
+            fingerprint = key.fingerprint
+        if not fingerprint in self:
+            raise KeyNotFoundError(
+                f"The key for {fingerprint} in not found in the keystore."
+            )
+        if type(revocation) == str:
+            revocation = revocation.encode("utf-8")
+
+        # Both the public and the secret key files should carry the revocation
+        for k in self.fingerprints_cache[fingerprint].values():
+            if k is None:
+                continue
+            revoked = apply_revocation(k.keypath, revocation)
+            _write_file(k.keypath, revoked, secret=k.keytype == "secret")
+
+        return self.get_key(fingerprint)
+
     def delete_key(self, fingerprint: str, whichkey="both"):
         """Deletes a given key based on the fingerprint.
 
@@ -247,23 +515,39 @@ class KeyStore:
             del self.fingerprints_cache[fingerprint]
 
     def _find_key_paths(self, keys):
-        "To find all the key paths"
+        "To find all the key paths, Cert objects are passed as is"
         final_key_paths = []
         for k in keys:
             if type(k) == str:  # Means fingerprint
                 key = self.get_key(k)
                 final_key_paths.append(key.keypath)
+            elif type(k) == Cert:
+                final_key_paths.append(k)
             else:
                 final_key_paths.append(k.keypath)
         return final_key_paths
 
-    def encrypt(self, keys, data, outputfile="", armor=True):
+    def encrypt(
+        self,
+        keys,
+        data,
+        outputfile="",
+        armor=True,
+        wildcard=False,
+        cipher=None,
+        compression=None,
+        aead=None,
+    ):
         """Encrypts the given data with the list of keys and returns the output.
 
-        :param keys: List of fingerprints or Key objects
+        :param keys: List of fingerprints, Key or Cert objects
         :param data: data to be encrtypted, either str or bytes
         :param outputfile: If provided the output will be wriiten in the location.
         :param armor: Default is True, for armored output.
+        :param wildcard: Default is False, if True the recipients are hidden in the output.
+        :param cipher: The symmetric algorithm, default is the preferred one of the recipients.
+        :param compression: none, zip, zlib or bzip2, or a tuple of the name and the level.
+        :param aead: The AEAD mode, eax or ocb, default is None.
         """
         if type(keys) != list:
             finalkeys = [
@@ -272,13 +556,16 @@ class KeyStore:
         else:
             finalkeys = keys
         final_key_paths = self._find_key_paths(finalkeys)
+        options = {"cipher": cipher, "compression": compression, "aead": aead}
         # Check if we return data
         if type(data) == str:
             finaldata = data.encode("utf-8")
         else:
             finaldata = data
         if not outputfile:
-            return encrypt_bytes_to_bytes(final_key_paths, finaldata, armor)
+            return encrypt_bytes_to_bytes(
+                final_key_paths, finaldata, armor, wildcard, **options
+            )
 
         # For encryption to a file
         if type(outputfile) == str:
@@ -286,31 +573,67 @@ class KeyStore:
         else:
             encrypted_file = outputfile
 
-        encrypt_bytes_to_file(final_key_paths, finaldata, encrypted_file, armor)
+        encrypt_bytes_to_file(
+            final_key_paths, finaldata, encrypted_file, armor, wildcard, **options
+        )
         return True
 
+    def _find_decryption_keys(self, keys):
+        "Returns the secret key paths for the list of keys, or all the secret keys for None"
+        if keys is None:  # Means all the secret keys in the store
+            return [
+                value["secret"].keypath
+                for value in self.fingerprints_cache.values()
+                if value["secret"]
+            ]
+        return [self._get_secret_key(key).keypath for key in keys]
+
     def decrypt(self, key, data, password=""):
         """Decrypts the given bytes and returns plain text bytes.
 
-        :param key: Fingerprint or secret Key object
+        :param key: Fingerprint or secret Key object, a list of them, or None for all the secret keys in the store
         :param data: Encrypted data in bytes.
-        :param password: Password for the secret key
+        :param password: Password for the secret key, or a dict of fingerprints to passwords
         """
+        if key is None or type(key) == list:
+            paths = self._find_decryption_keys(key)
+            result = decrypt_bytes_with_keys(paths, data, password)
+            return result.plaintext
+
         if type(key) == str:  # Means we have a fingerprint
             k = self.get_key(key, keytype="secret")
         else:
             k = key
 
         jp = Johnny(k.keypath)
-        return jp.decrypt_bytes(data, password)
-
-    def encrypt_file(self, keys, inputfilepath, outputfilepath, armor=True):
+        result = jp.decrypt_bytes(data, password)
+        return result.plaintext
+
+    def encrypt_file(
+        self,
+        keys,
+        inputfilepath,
+        outputfilepath,
+        armor=True,
+        wildcard=False,
+        cipher=None,
+        compression=None,
+        aead=None,
+        progress=None,
+        progress_interval=None,
+    ):
         """Encrypts the given data with the list of keys and writes in the output file.
 
-        :param keys: List of fingerprints or Key objects
+        :param keys: List of fingerprints, Key or Cert objects
         :param inputfilepath: Path of the input file to be encrypted
         :param outputfilepath: output file path
         :param armor: Default is True, for armored output.
+        :param wildcard: Default is False, if True the recipients are hidden in the output.
+        :param cipher: The symmetric algorithm, default is the preferred one of the recipients.
+        :param compression: none, zip, zlib or bzip2, or a tuple of the name and the level.
+        :param aead: The AEAD mode, eax or ocb, default is None.
+        :param progress: Callable called with the bytes read and the total size, return False to cancel.
+        :param progress_interval: Number of bytes between the calls of progress, default is 1MiB.
         """
         if not os.path.exists(inputfilepath):
             raise FileNotFoundError(f"{inputfilepath} can not be found.")
@@ -334,22 +657,38 @@ class KeyStore:
         else:
             encrypted_file = outputfilepath
 
-        encrypt_file_internal(final_key_paths, inputfile, encrypted_file, armor)
+        encrypt_file_internal(
+            final_key_paths,
+            inputfile,
+            encrypted_file,
+            armor,
+            wildcard,
+            cipher=cipher,
+            compression=compression,
+            aead=aead,
+            progress=progress,
+            progress_interval=progress_interval,
+        )
         return True
 
-    def decrypt_file(self, key, encrypted_path, outputfile, password=""):
+    def decrypt_file(
+        self,
+        key,
+        encrypted_path,
+        outputfile,
+        password="",
+        progress=None,
+        progress_interval=None,
+    ):
         """Decryptes the given file to the output path.
 
-        :param key: Fingerprint or secret Key object
+        :param key: Fingerprint or secret Key object, a list of them, or None for all the secret keys in the store
         :param encrypted_path:: Path of the encrypted file
         :param outputfile: Decrypted output file path as str
-        :param password: Password for the secret key
+        :param password: Password for the secret key, or a dict of fingerprints to passwords
+        :param progress: Callable called with the bytes read and the total size, return False to cancel.
+        :param progress_interval: Number of bytes between the calls of progress, default is 1MiB.
         """
-        if type(key) == str:  # Means we have a fingerprint
-            k = self.get_key(key, keytype="secret")
-        else:
-            k = key
-
         if type(encrypted_path) == str:
             inputfile = encrypted_path.encode("utf-8")
         else:
@@ -360,8 +699,88 @@ class KeyStore:
         else:
             outputpath = outputfile
 
+        if key is None or type(key) == list:
+            paths = self._find_decryption_keys(key)
+            decrypt_file_with_keys(
+                paths,
+                inputfile,
+                outputpath,
+                password,
+                progress=progress,
+                progress_interval=progress_interval,
+            )
+            return True
+
+        if type(key) == str:  # Means we have a fingerprint
+            k = self.get_key(key, keytype="secret")
+        else:
+            k = key
+
         jp = Johnny(k.keypath)
-        return jp.decrypt_file(inputfile, outputpath, password)
+        jp.decrypt_file(
+            inputfile,
+            outputpath,
+            password,
+            progress=progress,
+            progress_interval=progress_interval,
+        )
+        return True
+
+    def encrypt_stream(
+        self,
+        keys,
+        input,
+        output,
+        armor=True,
+        wildcard=False,
+        cipher=None,
+        compression=None,
+        aead=None,
+    ):
+        """Encrypts the data read from the input file object to the output file object, without
+        keeping all of it in memory.
+
+        :param keys: List of fingerprints, Key or Cert objects
+        :param input: File-like object opened in binary mode, with a read() method
+        :param output: File-like object opened in binary mode, with a write() method
+        :param armor: Default is True, for armored output.
+        :param wildcard: Default is False, if True the recipients are hidden in the output.
+        :param cipher: The symmetric algorithm, default is the preferred one of the recipients.
+        :param compression: none, zip, zlib or bzip2, or a tuple of the name and the level.
+        :param aead: The AEAD mode, eax or ocb, default is None.
+        """
+        if type(keys) != list:
+            keys = [
+                keys,
+            ]
+        final_key_paths = self._find_key_paths(keys)
+        return encrypt_stream(
+            final_key_paths,
+            input,
+            output,
+            armor,
+            wildcard,
+            cipher=cipher,
+            compression=compression,
+            aead=aead,
+        )
+
+    def decrypt_stream(self, key, input, output, password=""):
+        """Decrypts the data read from the input file object to the output file object.
+
+        :param key: Fingerprint or secret Key object, a list of them, or None for all the secret keys in the store
+        :param input: File-like object opened in binary mode, with a read() method
+        :param output: File-like object opened in binary mode, with a write() method
+        :param password: Password for the secret key, or a dict of fingerprints to passwords
+
+        :returns: DecryptionResult
+        """
+        if key is not None and type(key) != list:
+            key = [
+                key,
+            ]
+        paths = self._find_decryption_keys(key)
+        return decrypt_stream(paths, input, output, password)
 
     def sign(self, key, data, password):
         """Signs the given data with the key.
@@ -382,32 +801,51 @@ class KeyStore:
         jp = Johnny(k.keypath)
         return jp.sign_bytes_detached(data, password)
 
+    def _find_verification_keys(self, keys):
+        "Returns the public key paths (or Cert objects) to verify the signatures"
+        if keys is None:  # Means all the public keys in the store
+            return [
+                value["public"].keypath
+                for value in self.fingerprints_cache.values()
+                if value["public"]
+            ]
+        if type(keys) != list:
+            keys = [
+                keys,
+            ]
+        return self._find_key_paths(keys)
+
     def verify(self, key, data, signature):
         """Verifies the given data and the signature
 
-        :param key: Fingerprint or public Key object
+        :param key: Fingerprint, public Key or Cert object, a list of them, or None for all the keys in the store
         :param data: Data to be signed.
         :param signature: Signature text
 
-        :returns: Boolean
+        :returns: VerificationResult, which is True for a good signature
         """
-        if type(key) == str:  # Means we have a fingerprint
-            k = self.get_key(key, keytype="public")
-        else:
-            k = key
-
+        final_key_paths = self._find_verification_keys(key)
         if type(data) == str:
             data = data.encode("utf-8")
-        jp = Johnny(k.keypath)
-        return jp.verify_bytes(data, signature.encode("utf-8"))
-
-    def sign_file(self, key, filepath, password, write=False):
+        return verify_bytes_detached(final_key_paths, data, signature.encode("utf-8"))
+
+    def sign_file(
+        self,
+        key,
+        filepath,
+        password,
+        write=False,
+        progress=None,
+        progress_interval=None,
+    ):
         """Signs the given data with the key. It also writes filename.asc in the same directory of the file as the signature if write value is True.
 
         :param key: Fingerprint or secret Key object
         :param filepath: str value of the path to the file.
         :param password: Password of the secret key file.
         :param wrtie: boolean value (default False), determines if we should write the signature to a file.
+        :param progress: Callable called with the bytes read and the total size, return False to cancel.
+        :param progress_interval: Number of bytes between the calls of progress, default is 1MiB.
 
         :returns: The signature as string
         """
@@ -421,29 +859,30 @@ class KeyStore:
         else:
             filepath_in_bytes = filepath
         jp = Johnny(k.keypath)
-        signature = jp.sign_file_detached(filepath_in_bytes, password)
+        signature = jp.sign_file_detached(
+            filepath_in_bytes,
+            password,
+            progress=progress,
+            progress_interval=progress_interval,
+        )
 
         # Now check if we have to write the file on disk
         if write:
             sig_file_name = filepath + ".asc"
-            with open(sig_file_name, "w") as fobj:
-                fobj.write(signature)
+            _write_file(sig_file_name, signature)
 
         return signature
 
     def verify_file(self, key, filepath, signature_path):
         """Verifies the given filepath based on the signature file.
 
-        :param key: Fingerprint or public Key object
+        :param key: Fingerprint, public Key or Cert object, a list of them, or None for all the keys in the store
         :param filepath: File to be verified.
         :param signature_path: Path to the signature file.
 
-        :returns: Boolean
+        :returns: VerificationResult, which is True for a good signature
         """
-        if type(key) == str:  # Means we have a fingerprint
-            k = self.get_key(key, keytype="public")
-        else:
-            k = key
+        final_key_paths = self._find_verification_keys(key)
 
         if not os.path.exists(signature_path):
             raise FileNotFoundError(
@@ -458,5 +897,4 @@ class KeyStore:
 
         if type(filepath) == str:
             filepath = filepath.encode("utf-8")
-        jp = Johnny(k.keypath)
-        return jp.verify_file(filepath, signature_in_bytes)
+        return verify_file_detached(final_key_paths, filepath, signature_in_bytes)
@@ -1,2 +1,14 @@
+from .johnnycanencrypt import (
+    CryptoError,
+    WrongPasswordError,
+    NoDecryptionKeyError,
+    MalformedMessageError,
+    KeyParseError,
+    NoSigningKeyError,
+    PolicyRejectedError,
+    CancelledError,
+)
+
+
 class KeyNotFoundError(Exception):
     pass
@@ -1,3 +1,5 @@
+use pyo3::class::basic::PyObjectProtocol;
+use pyo3::create_exception;
 use pyo3::exceptions::*;
 use pyo3::prelude::*;
 use pyo3::types::PyBytes;
@@ -5,11 +7,17 @@ use pyo3::types::{PyDict, PyList};
 use pyo3::wrap_pyfunction;
 
 use std::collections::HashMap;
-use std::fs::File;
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
 use std::io;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 extern crate anyhow;
 
@@ -19,44 +27,635 @@ use crate::openpgp::armor;
 use openpgp::armor::{Kind, Writer};
 
 use crate::openpgp::crypto::{KeyPair, SessionKey};
+use crate::openpgp::packet::key;
+use crate::openpgp::packet::key::Key4;
+use crate::openpgp::packet::signature::SignatureBuilder;
 use crate::openpgp::parse::stream::{
-    DecryptionHelper, DecryptorBuilder, DetachedVerifierBuilder, MessageLayer, MessageStructure,
-    VerificationHelper,
+    DecryptionHelper, Decryptor, DecryptorBuilder, DetachedVerifier, DetachedVerifierBuilder,
+    GoodChecksum, MessageLayer, MessageStructure, VerificationError, VerificationHelper,
+    VerifierBuilder,
 };
 
 use crate::openpgp::parse::Parse;
 use crate::openpgp::policy::NullPolicy as NP;
 use crate::openpgp::policy::Policy;
 use crate::openpgp::policy::StandardPolicy as P;
-use crate::openpgp::serialize::stream::{Encryptor, LiteralWriter, Message, Signer};
+use crate::openpgp::serialize::stream::{
+    Compressor, Encryptor, LiteralWriter, Message, Recipient, Signer,
+};
 use crate::openpgp::serialize::Marshal;
 use crate::openpgp::serialize::MarshalInto;
+use crate::openpgp::types::AEADAlgorithm;
+use crate::openpgp::types::CompressionAlgorithm;
+use crate::openpgp::types::CompressionLevel;
+use crate::openpgp::types::Curve;
+use crate::openpgp::types::HashAlgorithm;
 use crate::openpgp::types::KeyFlags;
+use crate::openpgp::types::ReasonForRevocation;
+use crate::openpgp::types::RevocationStatus;
+use crate::openpgp::types::SignatureType;
 use crate::openpgp::types::SymmetricAlgorithm;
 use openpgp::cert::prelude::*;
+use openpgp::cert::Preferences;
+
+// Base class for all the errors raised from the Rust side.
+create_exception!(johnnycanencrypt, CryptoError, PyException);
+// The password of the secret key is wrong.
+create_exception!(johnnycanencrypt, WrongPasswordError, CryptoError);
+// None of the available secret keys can decrypt the message.
+create_exception!(johnnycanencrypt, NoDecryptionKeyError, CryptoError);
+// The message (or signature) can not be parsed or was modified.
+create_exception!(johnnycanencrypt, MalformedMessageError, CryptoError);
+// The key file/data can not be parsed as an OpenPGP certificate.
+create_exception!(johnnycanencrypt, KeyParseError, CryptoError);
+// The certificate does not have any usable signing (sub)key.
+create_exception!(johnnycanencrypt, NoSigningKeyError, CryptoError);
+// The certificate or the operation was rejected by the policy.
+create_exception!(johnnycanencrypt, PolicyRejectedError, CryptoError);
+// The operation was cancelled from the progress callback.
+create_exception!(johnnycanencrypt, CancelledError, CryptoError);
+
+/// Maps an error from sequoia to the right Python exception.
+fn to_pyerr(err: anyhow::Error) -> PyErr {
+    // Errors raised from the Python callbacks are passed as they are
+    let err = match err.downcast::<PyErr>() {
+        Ok(pyerr) => return pyerr,
+        Err(err) => err,
+    };
+    // sequoia wraps the real error inside io::Error while streaming
+    let inner = match err.downcast_ref::<io::Error>() {
+        Some(ioerr) => ioerr
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<openpgp::Error>()),
+        None => err.downcast_ref::<openpgp::Error>(),
+    };
+    let msg = err.to_string();
+    match inner {
+        Some(openpgp::Error::PolicyViolation(..)) => PolicyRejectedError::new_err(msg),
+        Some(openpgp::Error::InvalidPassword) => WrongPasswordError::new_err(msg),
+        Some(openpgp::Error::MissingSessionKey(..)) => NoDecryptionKeyError::new_err(msg),
+        Some(openpgp::Error::MalformedCert(..)) | Some(openpgp::Error::UnsupportedCert(..)) => {
+            KeyParseError::new_err(msg)
+        }
+        Some(openpgp::Error::MalformedMessage(..))
+        | Some(openpgp::Error::MalformedPacket(..))
+        | Some(openpgp::Error::MalformedMPI(..))
+        | Some(openpgp::Error::ManipulatedMessage) => MalformedMessageError::new_err(msg),
+        _ => match err.downcast::<io::Error>() {
+            // Errors raised from the Python file objects come wrapped inside io::Error
+            Ok(ioerr) if ioerr.get_ref().map_or(false, |e| e.is::<PyErr>()) => {
+                match ioerr.into_inner().map(|e| e.downcast::<PyErr>()) {
+                    Some(Ok(pyerr)) => *pyerr,
+                    _ => CryptoError::new_err(msg),
+                }
+            }
+            // Plain I/O errors, like missing files, stay as OSError
+            Ok(ioerr) if ioerr.get_ref().is_none() => PyErr::from(ioerr),
+            _ => CryptoError::new_err(msg),
+        },
+    }
+}
+
+/// Reads a Cert from the given path.
+fn read_cert(path: &str) -> PyResult<openpgp::Cert> {
+    if !std::fs::metadata(path).is_ok() {
+        return Err(PyFileNotFoundError::new_err(format!(
+            "{} is not found.",
+            path
+        )));
+    }
+    openpgp::Cert::from_file(path)
+        .map_err(|e| KeyParseError::new_err(format!("Can not parse {}: {}", path, e)))
+}
+
+/// Returns the secret key (TSK) as armored string.
+fn armored_secret(cert: &openpgp::Cert) -> PyResult<String> {
+    let mut buf = Vec::new();
+    let mut buffer = Vec::new();
+
+    let mut writer = Writer::new(&mut buf, Kind::SecretKey)?;
+    cert.as_tsk().serialize(&mut buffer).map_err(to_pyerr)?;
+    writer.write_all(&buffer)?;
+    writer.finalize()?;
+    Ok(String::from_utf8(buf)?)
+}
+
+/// Returns the public key as armored string.
+fn armored_public(cert: &openpgp::Cert) -> PyResult<String> {
+    let armored = cert.armored().to_vec().map_err(to_pyerr)?;
+    Ok(String::from_utf8(armored)?)
+}
+
+/// An OpenPGP certificate (public or secret key), can be created from bytes or from a file.
+#[pyclass]
+#[derive(Debug, Clone)]
+struct Cert {
+    cert: openpgp::Cert,
+}
+
+#[pymethods]
+impl Cert {
+    /// Creates the Cert from armored or binary bytes.
+    #[new]
+    fn new(data: Vec<u8>) -> PyResult<Self> {
+        Cert::from_bytes(data)
+    }
+
+    #[staticmethod]
+    #[text_signature = "(data)"]
+    fn from_bytes(data: Vec<u8>) -> PyResult<Self> {
+        let cert = openpgp::Cert::from_bytes(&data)
+            .map_err(|e| KeyParseError::new_err(format!("Can not parse the key: {}", e)))?;
+        Ok(Cert { cert })
+    }
+
+    #[staticmethod]
+    #[text_signature = "(path)"]
+    fn from_file(path: String) -> PyResult<Self> {
+        let cert = read_cert(&path)?;
+        Ok(Cert { cert })
+    }
+
+    #[getter]
+    fn fingerprint(&self) -> String {
+        self.cert.fingerprint().to_hex()
+    }
+
+    #[getter]
+    fn is_secret(&self) -> bool {
+        self.cert.is_tsk()
+    }
+
+    /// Returns all the details of the key as a dictionary, evaluated under the given policy,
+    /// either "standard" (the default) or "null".
+    #[text_signature = "($self, policy=\"standard\")"]
+    fn details(&self, py: Python, policy: Option<String>) -> PyResult<PyObject> {
+        let p = get_policy(policy)?;
+        cert_details(py, &self.cert, p.as_ref())
+    }
+
+    /// Returns the armored key as str, pass secret=True for the secret key.
+    #[text_signature = "($self, secret=False)"]
+    fn as_armored(&self, secret: Option<bool>) -> PyResult<String> {
+        match secret {
+            Some(true) => {
+                self.ensure_secret()?;
+                armored_secret(&self.cert)
+            }
+            _ => armored_public(&self.cert),
+        }
+    }
+
+    /// Returns the binary key as bytes, pass secret=True for the secret key.
+    #[text_signature = "($self, secret=False)"]
+    fn as_bytes(&self, py: Python, secret: Option<bool>) -> PyResult<PyObject> {
+        let data = match secret {
+            Some(true) => {
+                self.ensure_secret()?;
+                self.cert.as_tsk().to_vec().map_err(to_pyerr)?
+            }
+            _ => self.cert.to_vec().map_err(to_pyerr)?,
+        };
+        Ok(PyBytes::new(py, &data).into())
+    }
+}
+
+impl Cert {
+    fn ensure_secret(&self) -> PyResult<()> {
+        if !self.cert.is_tsk() {
+            return Err(CryptoError::new_err(format!(
+                "{} does not have any secret key.",
+                self.cert.fingerprint().to_hex()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Either a Cert object or the path to the key file, all the functions accept both.
+struct CertOrPath(openpgp::Cert);
+
+impl<'source> FromPyObject<'source> for CertOrPath {
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        if let Ok(cert) = ob.extract::<PyRef<Cert>>() {
+            return Ok(CertOrPath(cert.cert.clone()));
+        }
+        let path: String = ob.extract()?;
+        Ok(CertOrPath(read_cert(&path)?))
+    }
+}
+
+/// Reads from a Python file-like object, which has a read() method returning bytes.
+struct PyReader(PyObject);
+
+impl io::Read for PyReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        Python::with_gil(|py| {
+            let data = self.0.call_method1(py, "read", (buf.len(),))?;
+            let data = data.as_ref(py).downcast::<PyBytes>()?.as_bytes();
+            if data.len() > buf.len() {
+                return Err(PyValueError::new_err(
+                    "read() returned more bytes than asked.",
+                ));
+            }
+            buf[..data.len()].copy_from_slice(data);
+            Ok(data.len())
+        })
+        .map_err(|e: PyErr| io::Error::new(io::ErrorKind::Other, e))
+    }
+}
+
+/// Writes to a Python file-like object, which has a write() method taking bytes.
+struct PyWriter(PyObject);
+
+impl io::Write for PyWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Python::with_gil(|py| {
+            let written = self.0.call_method1(py, "write", (PyBytes::new(py, buf),))?;
+            // Some of the file-like objects return None instead of the number of bytes
+            Ok(written.extract::<Option<usize>>(py)?.unwrap_or(buf.len()))
+        })
+        .map_err(|e: PyErr| io::Error::new(io::ErrorKind::Other, e))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Python::with_gil(|py| {
+            if self.0.as_ref(py).hasattr("flush")? {
+                self.0.call_method0(py, "flush")?;
+            }
+            Ok(())
+        })
+        .map_err(|e: PyErr| io::Error::new(io::ErrorKind::Other, e))
+    }
+}
+
+/// By default the progress callback is called after every 1MiB of the input.
+const PROGRESS_INTERVAL: u64 = 1024 * 1024;
+
+/// Counts the bytes read from the input, and calls the Python progress callback with the number
+/// of bytes read and the total size, after every interval and at the end of the input. The
+/// callback can return False (or raise an exception) to cancel the operation.
+struct ProgressReader<R: io::Read> {
+    inner: R,
+    callback: Option<PyObject>,
+    interval: u64,
+    total: u64,
+    done: u64,
+    // The number of bytes at the last call of the callback
+    reported: Option<u64>,
+}
+
+impl<R: io::Read> ProgressReader<R> {
+    fn new(inner: R, total: u64, callback: Option<PyObject>, interval: Option<u64>) -> Self {
+        ProgressReader {
+            inner,
+            callback,
+            interval: interval.unwrap_or(PROGRESS_INTERVAL),
+            total,
+            done: 0,
+            reported: None,
+        }
+    }
+
+    fn report(&mut self) -> PyResult<()> {
+        self.reported = Some(self.done);
+        let callback = match self.callback {
+            Some(ref callback) => callback,
+            None => return Ok(()),
+        };
+        Python::with_gil(|py| {
+            let result = callback.call1(py, (self.done, self.total))?;
+            // Only an explicit False cancels, None (or anything else) continues
+            match result.extract::<bool>(py) {
+                Ok(false) => Err(CancelledError::new_err("The operation was cancelled.")),
+                _ => Ok(()),
+            }
+        })
+    }
+}
+
+impl<R: io::Read> io::Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let size = self.inner.read(buf)?;
+        self.done += size as u64;
+        let report = if size == 0 {
+            self.reported != Some(self.done)
+        } else {
+            self.done - self.reported.unwrap_or(0) >= self.interval
+        };
+        if report {
+            self.report()
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        }
+        Ok(size)
+    }
+}
+
+/// Counts the temporary output files of this process, to keep their names unique.
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Writes the output to a temporary file in the same directory, which is renamed to the output
+/// path by persist() only after the whole operation succeeded, for decryption that is after the
+/// message is authenticated. The temporary file is removed if it is dropped before, so a failed
+/// or cancelled operation never leaves a partial output.
+struct OutputFile {
+    file: File,
+    temppath: PathBuf,
+    path: PathBuf,
+    persisted: bool,
+}
+
+impl OutputFile {
+    fn create<T: AsRef<Path>>(path: T) -> PyResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        let name = path.file_name().ok_or_else(|| {
+            PyValueError::new_err(format!("{} is not a file path.", path.display()))
+        })?;
+        loop {
+            let mut tempname = OsString::from(".");
+            tempname.push(name);
+            tempname.push(format!(
+                ".{}.{}.tmp",
+                std::process::id(),
+                TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+            ));
+            let temppath = path.with_file_name(tempname);
+            match OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&temppath)
+            {
+                Ok(file) => {
+                    return Ok(OutputFile {
+                        file,
+                        temppath,
+                        path,
+                        persisted: false,
+                    })
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Moves the complete temporary file to the output path.
+    fn persist(mut self) -> PyResult<()> {
+        self.file.sync_all()?;
+        fs::rename(&self.temppath, &self.path)?;
+        self.persisted = true;
+        Ok(())
+    }
+}
+
+impl io::Write for OutputFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl Drop for OutputFile {
+    fn drop(&mut self) {
+        if !self.persisted {
+            let _ = fs::remove_file(&self.temppath);
+        }
+    }
+}
+
+/// Decrypts the secret key material in place with the given password, if it is encrypted.
+fn decrypt_secret<R: key::KeyRole>(
+    key: &mut openpgp::packet::Key<key::SecretParts, R>,
+    password: &str,
+) -> PyResult<()> {
+    if key.secret().is_encrypted() {
+        let algo = key.pk_algo();
+        let fingerprint = key.fingerprint().to_hex();
+        key.secret_mut()
+            .decrypt_in_place(algo, &openpgp::crypto::Password::from(password))
+            .map_err(|_| {
+                WrongPasswordError::new_err(format!("Wrong password for the key {}.", fingerprint))
+            })?;
+    }
+    Ok(())
+}
+
+/// Decrypts the secret key material with the given password and returns the KeyPair.
+fn get_keypair(
+    key: &openpgp::packet::Key<key::SecretParts, key::UnspecifiedRole>,
+    password: &str,
+) -> PyResult<KeyPair> {
+    let mut key = key.clone();
+    decrypt_secret(&mut key, password)?;
+    key.into_keypair().map_err(to_pyerr)
+}
+
+/// Decrypts the secret key material with the old password, and encrypts it again with the new
+/// password, or keeps it unencrypted if there is no new password.
+fn reencrypt_secret<R: key::KeyRole>(
+    mut key: openpgp::packet::Key<key::SecretParts, R>,
+    password: &str,
+    newpassword: &Option<openpgp::crypto::Password>,
+) -> PyResult<openpgp::packet::Key<key::SecretParts, R>> {
+    decrypt_secret(&mut key, password)?;
+    if let Some(newpass) = newpassword {
+        key.secret_mut()
+            .encrypt_in_place(newpass)
+            .map_err(to_pyerr)?;
+    }
+    Ok(key)
+}
+
+/// How many times the password callback is asked for the password of a key.
+const PASSWORD_ATTEMPTS: usize = 3;
+
+/// The password(s) to unlock the secret keys, either the same password for all the keys, a dict
+/// of the fingerprints to the passwords, or a callable which returns the password of the key.
+enum KeyPassword {
+    Single(String),
+    Map(HashMap<String, String>),
+    Callback(PyObject),
+}
+
+impl<'source> FromPyObject<'source> for KeyPassword {
+    fn extract(obj: &'source PyAny) -> PyResult<Self> {
+        if let Ok(password) = obj.extract::<String>() {
+            return Ok(KeyPassword::Single(password));
+        }
+        if obj.is_callable() {
+            return Ok(KeyPassword::Callback(obj.into()));
+        }
+        let passwords: HashMap<String, String> = obj.extract()?;
+        Ok(KeyPassword::Map(
+            passwords
+                .into_iter()
+                .map(|(fp, password)| (fp.to_uppercase(), password))
+                .collect(),
+        ))
+    }
+}
+
+impl KeyPassword {
+    /// Decrypts the secret key material and returns the KeyPair. The password is looked up via
+    /// the fingerprint of the Cert or the key, or the callback is called with the fingerprint of
+    /// the key, the primary user ID and the number of the attempt, till it returns the right
+    /// password or None.
+    fn get_keypair(
+        &self,
+        key: &openpgp::packet::Key<key::SecretParts, key::UnspecifiedRole>,
+        certfp: &openpgp::Fingerprint,
+        uid: Option<&str>,
+    ) -> PyResult<KeyPair> {
+        match self {
+            KeyPassword::Single(password) => get_keypair(key, password),
+            KeyPassword::Map(passwords) => {
+                let password = passwords
+                    .get(&certfp.to_hex())
+                    .or_else(|| passwords.get(&key.fingerprint().to_hex()))
+                    .map(|password| password.as_str());
+                get_keypair(key, password.unwrap_or(""))
+            }
+            KeyPassword::Callback(callback) => {
+                // No need to ask for the password of an unencrypted key
+                if !key.secret().is_encrypted() {
+                    return get_keypair(key, "");
+                }
+                let fingerprint = key.fingerprint().to_hex();
+                Python::with_gil(|py| {
+                    for attempt in 0..PASSWORD_ATTEMPTS {
+                        let password: Option<String> = callback
+                            .call1(py, (fingerprint.clone(), uid, attempt))?
+                            .extract(py)?;
+                        let password = match password {
+                            Some(password) => password,
+                            None => break,
+                        };
+                        match get_keypair(key, &password) {
+                            Err(e) if e.is_instance::<WrongPasswordError>(py) => continue,
+                            result => return result,
+                        }
+                    }
+                    Err(WrongPasswordError::new_err(format!(
+                        "Wrong password for the key {}.",
+                        fingerprint
+                    )))
+                })
+            }
+        }
+    }
+}
 
+/// Returns the value of the primary user ID of the Cert, if any.
+fn primary_userid_value(cert: &openpgp::Cert) -> Option<String> {
+    let p = &NP::new();
+    let vc = cert.with_policy(p, None).ok()?;
+    let ua = vc.primary_userid().ok()?;
+    Some(String::from_utf8_lossy(ua.value()).to_string())
+}
+
+#[derive(Default)]
 struct Helper {
-    keys: HashMap<openpgp::KeyID, KeyPair>,
+    // Secret (sub)keys along with the fingerprint and the primary user ID of their Cert, these
+    // are only unlocked when the message has a PKESK for the key
+    secrets: HashMap<
+        openpgp::KeyID,
+        (
+            openpgp::packet::Key<key::SecretParts, key::UnspecifiedRole>,
+            openpgp::Fingerprint,
+            Option<String>,
+        ),
+    >,
+    keypassword: Option<KeyPassword>,
+    // To decrypt the SKESKs, when none of the keys can decrypt the message
+    password: Option<openpgp::crypto::Password>,
+    // Certs to verify the signatures inside of the encrypted message
+    certs: Vec<openpgp::Cert>,
+    // The details of the signatures inside of the encrypted message
+    signatures: Vec<SignatureInfo>,
+    // The following are filled in while decrypting the message
+    recipients: Vec<openpgp::KeyID>,
+    decrypted_by: Option<openpgp::Fingerprint>,
+    session_key: Option<(SymmetricAlgorithm, SessionKey)>,
+    // The header of the literal data packet, the body is streamed to the caller
+    literal: Option<openpgp::packet::Literal>,
 }
 
 impl Helper {
-    /// Creates a Helper for the given Certs with appropriate secrets.
-    fn new(p: &dyn Policy, cert: &openpgp::Cert, pass: &str) -> Self {
-        // Map (sub)KeyIDs to secrets.
-        let mut keys = HashMap::new();
+    /// Creates a Helper for the given Cert with appropriate secrets.
+    fn new(p: &dyn Policy, cert: &openpgp::Cert, pass: KeyPassword) -> PyResult<Self> {
+        Helper::from_certs(p, std::slice::from_ref(cert), pass).map_err(|_| {
+            NoDecryptionKeyError::new_err(format!(
+                "{} does not have any secret key to decrypt.",
+                cert.fingerprint().to_hex()
+            ))
+        })
+    }
+
+    /// Creates a Helper for all the secret keys of the given Certs, the passwords are only
+    /// used for the keys which can decrypt the message.
+    fn from_certs(
+        p: &dyn Policy,
+        certs: &[openpgp::Cert],
+        keypassword: KeyPassword,
+    ) -> PyResult<Self> {
+        // Map (sub)KeyIDs to secrets, only the encryption (sub)keys can decrypt a message, the
+        // signing and authentication keys are never tried (or asked a password for).
+        let mut secrets = HashMap::new();
+        let mode = KeyFlags::default()
+            .set_storage_encryption(true)
+            .set_transport_encryption(true);
+
+        for cert in certs {
+            let uid = primary_userid_value(cert);
+            for ka in cert.keys().with_policy(p, None).secret().key_flags(&mode) {
+                secrets.insert(
+                    ka.key().keyid(),
+                    (ka.key().clone(), cert.fingerprint(), uid.clone()),
+                );
+            }
+        }
+        if secrets.is_empty() {
+            return Err(NoDecryptionKeyError::new_err(
+                "None of the keys have any secret key to decrypt.",
+            ));
+        }
+        Ok(Helper {
+            secrets,
+            keypassword: Some(keypassword),
+            ..Default::default()
+        })
+    }
 
-        for ka in cert.keys().with_policy(p, None).secret() {
-            keys.insert(
-                ka.key().keyid(),
-                ka.key()
-                    .clone()
-                    .decrypt_secret(&openpgp::crypto::Password::from(pass))
-                    .unwrap()
-                    .into_keypair()
-                    .unwrap(),
-            );
+    /// Creates a Helper to decrypt only using the password (SKESK).
+    fn from_password(pass: &str) -> Self {
+        Helper {
+            password: Some(openpgp::crypto::Password::from(pass)),
+            ..Default::default()
         }
-        Helper { keys }
+    }
+
+    /// Sets the password to try on the SKESKs if none of the PKESKs match.
+    fn set_password(mut self, pass: Option<String>) -> Self {
+        self.password = pass.map(openpgp::crypto::Password::from);
+        self
+    }
+
+    /// Sets the Certs to verify the signatures of a signed and encrypted message.
+    fn set_signers(mut self, certs: Vec<openpgp::Cert>) -> Self {
+        self.certs = certs;
+        self
+    }
+
+    /// Unlocks the secret key for the given KeyID, if we have one.
+    fn get_keypair(&self, keyid: &openpgp::KeyID) -> Option<PyResult<KeyPair>> {
+        let (key, certfp, uid) = self.secrets.get(keyid)?;
+        Some(match self.keypassword {
+            Some(ref keypassword) => keypassword.get_keypair(key, certfp, uid.as_deref()),
+            None => get_keypair(key, ""),
+        })
     }
 }
 
@@ -64,93 +663,247 @@ impl DecryptionHelper for Helper {
     fn decrypt<D>(
         &mut self,
         pkesks: &[openpgp::packet::PKESK],
-        _skesks: &[openpgp::packet::SKESK],
+        skesks: &[openpgp::packet::SKESK],
         sym_algo: Option<SymmetricAlgorithm>,
         mut decrypt: D,
     ) -> openpgp::Result<Option<openpgp::Fingerprint>>
     where
         D: FnMut(SymmetricAlgorithm, &SessionKey) -> bool,
     {
+        self.recipients = pkesks.iter().map(|p| p.recipient().clone()).collect();
+        let mut wrong_password = false;
         // Try each PKESK until we succeed.
         for pkesk in pkesks {
-            let keyid = pkesk.recipient();
-            // If the keyid is not present, we should just skip to next pkesk
-            let keypair = match self.keys.get_mut(&keyid) {
-                Some(keypair) => keypair,
-                _ => {
-                    continue;
-                }
+            let keyids = if pkesk.recipient().is_wildcard() {
+                // The recipient is hidden, so we try all the secret encryption keys of the same
+                // algorithm
+                self.secrets
+                    .iter()
+                    .filter(|(_, (key, _, _))| key.pk_algo() == pkesk.pk_algo())
+                    .map(|(keyid, _)| keyid.clone())
+                    .collect()
+            } else {
+                vec![pkesk.recipient().clone()]
             };
-            let fp = keypair.public().fingerprint();
-            // now get the algo
-            if pkesk
-                .decrypt(keypair, sym_algo)
-                .map(|(algo, session_key)| decrypt(algo, &session_key))
-                .unwrap_or(false)
-            {
-                return Ok(Some(fp));
+            for keyid in keyids {
+                // If we do not have the secret key, we should just skip to the next one
+                let mut keypair = match self.get_keypair(&keyid) {
+                    Some(Ok(keypair)) => keypair,
+                    Some(Err(e)) => {
+                        if Python::with_gil(|py| e.is_instance::<WrongPasswordError>(py)) {
+                            wrong_password = true;
+                            continue;
+                        }
+                        // Errors from the password callback stop the decryption
+                        return Err(e.into());
+                    }
+                    None => continue,
+                };
+                let fp = keypair.public().fingerprint();
+                // now get the algo
+                if let Ok((algo, session_key)) = pkesk.decrypt(&mut keypair, sym_algo) {
+                    if decrypt(algo, &session_key) {
+                        self.decrypted_by = Some(fp.clone());
+                        self.session_key = Some((algo, session_key));
+                        return Ok(Some(fp));
+                    }
+                }
+            }
+        }
+        // Now fall back to the password, if we have any
+        if let Some(ref password) = self.password {
+            for skesk in skesks {
+                if let Ok((algo, session_key)) = skesk.decrypt(password) {
+                    if decrypt(algo, &session_key) {
+                        self.session_key = Some((algo, session_key));
+                        return Ok(None);
+                    }
+                }
+            }
+            if !skesks.is_empty() {
+                return Err(openpgp::Error::InvalidPassword.into());
             }
         }
-        Ok(None)
+        if wrong_password {
+            return Err(openpgp::Error::InvalidPassword.into());
+        }
+        Err(openpgp::Error::MissingSessionKey(
+            "No matching secret key found to decrypt the message.".into(),
+        )
+        .into())
     }
 }
 
 impl VerificationHelper for Helper {
+    fn inspect(&mut self, pp: &openpgp::parse::PacketParser) -> openpgp::Result<()> {
+        if let openpgp::Packet::Literal(ref literal) = pp.packet {
+            self.literal = Some(literal.clone());
+        }
+        Ok(())
+    }
     fn get_certs(&mut self, _ids: &[openpgp::KeyHandle]) -> openpgp::Result<Vec<openpgp::Cert>> {
-        Ok(vec![]) // Feed the Certs to the verifier here.
+        Ok(self.certs.clone())
     }
-    fn check(&mut self, _structure: MessageStructure) -> openpgp::Result<()> {
+    fn check(&mut self, structure: MessageStructure) -> openpgp::Result<()> {
+        // We only record the signatures here, the caller decides what to do with an unsigned
+        // message or bad signatures.
+        for layer in structure.into_iter() {
+            if let MessageLayer::SignatureGroup { results } = layer {
+                for result in results {
+                    self.signatures.push(SignatureInfo::from_result(&result));
+                }
+            }
+        }
         Ok(())
     }
 }
 
+/// The details of a signature, filled in while verifying it.
+#[derive(Debug, Clone)]
+struct SignatureInfo {
+    // good, bad, malformed, unknown_key, unbound_key, expired_key, revoked_key or bad_key
+    status: &'static str,
+    // The fingerprint of the cert which made the signature, None for an unknown key
+    fingerprint: Option<String>,
+    // The fingerprint of the (sub)key which made the signature
+    signing_key: Option<String>,
+    // The issuer of the signature
+    keyid: Option<String>,
+    creation_time: Option<u64>,
+    hash_algo: String,
+    // Why the signature is not good
+    error: Option<String>,
+}
+
+impl SignatureInfo {
+    fn new(sig: &openpgp::packet::Signature, status: &'static str) -> Self {
+        SignatureInfo {
+            status,
+            fingerprint: None,
+            signing_key: None,
+            keyid: sig
+                .get_issuers()
+                .into_iter()
+                .next()
+                .map(|handle| openpgp::KeyID::from(handle).to_hex()),
+            creation_time: sig.signature_creation_time().map(to_timestamp),
+            hash_algo: format!("{:?}", sig.hash_algo()),
+            error: None,
+        }
+    }
+
+    fn set_key(mut self, ka: &ValidErasedKeyAmalgamation<key::PublicParts>) -> Self {
+        self.fingerprint = Some(ka.cert().fingerprint().to_hex());
+        self.signing_key = Some(ka.key().fingerprint().to_hex());
+        self
+    }
+
+    fn set_error(mut self, error: &anyhow::Error) -> Self {
+        self.error = Some(error.to_string());
+        self
+    }
+
+    /// Creates the SignatureInfo from the result of the signature verification.
+    fn from_result(result: &Result<GoodChecksum, VerificationError>) -> Self {
+        match result {
+            Ok(good) => SignatureInfo::new(good.sig, "good").set_key(&good.ka),
+            Err(VerificationError::MalformedSignature { sig, error }) => {
+                SignatureInfo::new(sig, "malformed").set_error(error)
+            }
+            Err(VerificationError::MissingKey { sig }) => SignatureInfo::new(sig, "unknown_key"),
+            Err(VerificationError::UnboundKey { sig, cert, error }) => {
+                let mut info = SignatureInfo::new(sig, "unbound_key").set_error(error);
+                info.fingerprint = Some(cert.fingerprint().to_hex());
+                info
+            }
+            Err(VerificationError::BadKey { sig, ka, error }) => {
+                let status = if matches!(ka.revocation_status(), RevocationStatus::Revoked(_))
+                    || matches!(ka.cert().revocation_status(), RevocationStatus::Revoked(_))
+                {
+                    "revoked_key"
+                } else if ka.alive().is_err() || ka.cert().alive().is_err() {
+                    "expired_key"
+                } else {
+                    "bad_key"
+                };
+                SignatureInfo::new(sig, status).set_key(ka).set_error(error)
+            }
+            Err(VerificationError::BadSignature { sig, ka, error }) => {
+                SignatureInfo::new(sig, "bad").set_key(ka).set_error(error)
+            }
+        }
+    }
+
+    fn to_dict<'p>(&self, py: Python<'p>) -> PyResult<&'p PyDict> {
+        let pd = PyDict::new(py);
+        pd.set_item("status", self.status)?;
+        pd.set_item("good", self.status == "good")?;
+        pd.set_item("fingerprint", &self.fingerprint)?;
+        pd.set_item("signing_key", &self.signing_key)?;
+        pd.set_item("keyid", &self.keyid)?;
+        pd.set_item("creation_time", self.creation_time)?;
+        pd.set_item("hash_algo", &self.hash_algo)?;
+        pd.set_item("error", &self.error)?;
+        Ok(pd)
+    }
+}
+
+/// Verifies the signatures using the given Certs, and records the details of all of them.
 struct VHelper {
-    cert: openpgp::Cert,
+    certs: Vec<openpgp::Cert>,
+    signatures: Vec<SignatureInfo>,
 }
 
 impl VHelper {
-    /// Creates a VHelper for the given Cert for signature verification.
-    fn new(cert: &openpgp::Cert) -> Self {
-        let cloned = cert.clone();
-        VHelper { cert: cloned }
+    /// Creates a VHelper for the given Certs for signature verification.
+    fn new(certs: Vec<openpgp::Cert>) -> Self {
+        VHelper {
+            certs,
+            signatures: Vec::new(),
+        }
     }
 }
 
 impl VerificationHelper for VHelper {
-    fn get_certs(&mut self, _ids: &[openpgp::KeyHandle]) -> openpgp::Result<Vec<openpgp::Cert>> {
-        Ok(vec![self.cert.clone()]) // Feed the Certs to the verifier here.
+    fn get_certs(&mut self, ids: &[openpgp::KeyHandle]) -> openpgp::Result<Vec<openpgp::Cert>> {
+        // Only the Certs which have any of the (sub)keys which made the signatures
+        let ids: Vec<openpgp::KeyID> = ids.iter().cloned().map(openpgp::KeyID::from).collect();
+        Ok(self
+            .certs
+            .iter()
+            .filter(|cert| cert.keys().any(|ka| ids.contains(&ka.key().keyid())))
+            .cloned()
+            .collect())
     }
     fn check(&mut self, structure: MessageStructure) -> openpgp::Result<()> {
-        let mut good = false;
-        for (i, layer) in structure.into_iter().enumerate() {
-            match (i, layer) {
-                // First, we are interested in signatures over the
-                // data, i.e. level 0 signatures.
-                (0, MessageLayer::SignatureGroup { results }) => {
-                    // Finally, given a VerificationResult, which only says
-                    // whether the signature checks out mathematically, we apply
-                    // our policy.
-                    match results.into_iter().next() {
-                        Some(Ok(_)) => good = true,
-                        Some(Err(e)) => return Err(openpgp::Error::from(e).into()),
-                        None => return Err(anyhow::anyhow!("No signature")),
-                    }
+        // We record all the signatures, the VerificationResult tells if any of them is good.
+        for layer in structure.into_iter() {
+            if let MessageLayer::SignatureGroup { results } = layer {
+                for result in results {
+                    self.signatures.push(SignatureInfo::from_result(&result));
                 }
-                _ => return Err(anyhow::anyhow!("Unexpected message structure")),
             }
         }
-
-        if good {
-            Ok(()) // Good signature.
-        } else {
-            Err(anyhow::anyhow!("Signature verification failed"))
-        }
+        Ok(())
     }
 }
 
+/// Creates the verifier for the detached signature, to verify using the given Certs.
+fn get_detached_verifier<'a>(
+    p: &'a dyn Policy,
+    certs: Vec<openpgp::Cert>,
+    sig: &'a [u8],
+) -> PyResult<DetachedVerifier<'a, VHelper>> {
+    DetachedVerifierBuilder::from_bytes(sig)
+        .map_err(|e| MalformedMessageError::new_err(e.to_string()))?
+        .with_policy(p, None, VHelper::new(certs))
+        .map_err(to_pyerr)
+}
+
 // To create key pairs; from the given Cert
-fn get_keys(cert: &openpgp::cert::Cert, password: String) -> Vec<openpgp::crypto::KeyPair> {
+fn get_keys(cert: &openpgp::cert::Cert, password: &KeyPassword) -> PyResult<Vec<KeyPair>> {
     let p = &P::new();
+    let uid = primary_userid_value(cert);
     let mut keys = Vec::new();
     for key in cert
         .keys()
@@ -161,622 +914,2553 @@ fn get_keys(cert: &openpgp::cert::Cert, password: String) -> Vec<openpgp::crypto
         .secret()
         .map(|kd| kd.key())
     {
-        let mut key = key.clone();
-        let algo = key.pk_algo();
-
-        let _keypair = key
-            .secret_mut()
-            .decrypt_in_place(algo, &openpgp::crypto::Password::from(password.clone()))
-            .expect("decryption failed");
-        keys.push(key.into_keypair().unwrap());
+        keys.push(password.get_keypair(key, &cert.fingerprint(), uid.as_deref())?);
     }
-    keys
+    Ok(keys)
 }
 
 fn sign_bytes_detached_internal(
     cert: &openpgp::cert::Cert,
     input: &mut dyn io::Read,
-    password: String,
+    password: KeyPassword,
 ) -> PyResult<String> {
     // TODO: WHY?
     let mut input = input;
 
-    let mut keys = get_keys(cert, password);
+    let mut keys = get_keys(cert, &password)?;
 
-    if keys.len() == 0 {
-        return Err(PyAttributeError::new_err("No signing key is present."));
-    }
+    let first = match keys.pop() {
+        Some(key) => key,
+        None => return Err(NoSigningKeyError::new_err("No signing key is present.")),
+    };
 
     let mut result = Vec::new();
-    let mut sink = armor::Writer::new(&mut result, armor::Kind::Signature)
-        .expect("Failed to create armored writer.");
+    let mut sink = armor::Writer::new(&mut result, armor::Kind::Signature)?;
 
     // Stream an OpenPGP message.
     let message = Message::new(&mut sink);
 
     // Now, create a signer that emits the detached signature(s).
-    let mut signer = Signer::new(message, keys.pop().expect("No key for signing"));
+    let mut signer = Signer::new(message, first);
     for s in keys {
         signer = signer.add_signer(s);
     }
-    let mut signer = signer.detached().build().expect("Failed to create signer");
+    let mut signer = signer.detached().build().map_err(to_pyerr)?;
 
     // Copy all the data.
-    io::copy(&mut input, &mut signer).expect("Failed to sign data");
+    io::copy(&mut input, &mut signer).map_err(|e| to_pyerr(e.into()))?;
 
     // Finally, teardown the stack to ensure all the data is written.
-    signer.finalize().expect("Failed to write data");
+    signer.finalize().map_err(to_pyerr)?;
 
     // Finalize the armor writer.
-    sink.finalize().expect("Failed to write data");
+    sink.finalize()?;
 
-    Ok(String::from_utf8(result).unwrap())
+    Ok(String::from_utf8(result)?)
 }
 
-#[pyfunction]
-#[text_signature = "(certpath)"]
-fn parse_cert_file(py: Python, certpath: String) -> PyResult<(PyObject, String, bool)> {
-    let cert = openpgp::Cert::from_file(certpath).unwrap();
-    let plist = PyList::empty(py);
-    for ua in cert.userids() {
-        let pd = PyDict::new(py);
-        //println!("  {}", String::from_utf8_lossy(ua.value()));
-        pd.set_item("value", String::from_utf8_lossy(ua.value()))
-            .unwrap();
-        // If we have a name part in the UID
-        match ua.name() {
-            Ok(value) => match value {
-                Some(name) => {
-                    pd.set_item("name", name).unwrap();
-                }
-                _ => (),
-            },
-            Err(_) => (),
-        }
-        // If we have a comment part in the UID
-        match ua.comment() {
-            Ok(value) => match value {
-                Some(comment) => {
-                    pd.set_item("comment", comment).unwrap();
-                }
-                _ => (),
-            },
-            Err(_) => (),
-        }
-        // If we have a email part in the UID
-        match ua.email() {
-            Ok(value) => match value {
-                Some(email) => {
-                    pd.set_item("email", email).unwrap();
-                }
-                _ => (),
-            },
-            Err(_) => (),
-        }
-        // If we have a URI part in the UID
-        match ua.uri() {
-            Ok(value) => match value {
-                Some(uri) => {
-                    pd.set_item("uri", uri).unwrap();
-                }
-                _ => (),
-            },
-            Err(_) => (),
+/// Returns the text canonicalized for the cleartext signature, the trailing whitespace of the
+/// lines is removed, and the lines are joined with CRLF (without the last line ending).
+fn cleartext_canonical(lines: &[&str]) -> String {
+    lines
+        .iter()
+        .map(|line| line.trim_end_matches(|c| c == ' ' || c == '\t' || c == '\r'))
+        .collect::<Vec<&str>>()
+        .join("\r\n")
+}
+
+/// Signs the text with the cleartext signature framework, so that the text stays readable.
+fn sign_cleartext_internal(
+    cert: &openpgp::Cert,
+    text: &str,
+    password: KeyPassword,
+) -> PyResult<String> {
+    let signers = get_signing_keys(cert, password)?;
+    // The last line ending belongs to the armor of the signature
+    let text = text.strip_suffix('\n').unwrap_or(text);
+    let lines: Vec<&str> = text.split('\n').collect();
+    let canonical = cleartext_canonical(&lines);
+
+    let hash_algo = HashAlgorithm::SHA256;
+    let mut hash = hash_algo.context().map_err(to_pyerr)?;
+    hash.update(canonical.as_bytes());
+
+    let mut result = format!(
+        "-----BEGIN PGP SIGNED MESSAGE-----\nHash: {}\n\n",
+        hash_algo
+    );
+    for line in canonical.split("\r\n") {
+        // Dash escaping, so that no line can be taken as an armor line
+        if line.starts_with('-') {
+            result.push_str("- ");
         }
-        plist.append(pd).unwrap();
+        result.push_str(line);
+        result.push('\n');
     }
 
-    Ok((plist.into(), cert.fingerprint().to_hex(), cert.is_tsk()))
+    let mut sigs = Vec::new();
+    let mut writer = armor::Writer::new(&mut sigs, armor::Kind::Signature)?;
+    for mut signer in signers {
+        let sig = SignatureBuilder::new(SignatureType::Text)
+            .sign_hash(&mut signer, hash.clone())
+            .map_err(to_pyerr)?;
+        openpgp::Packet::from(sig)
+            .serialize(&mut writer)
+            .map_err(to_pyerr)?;
+    }
+    writer.finalize()?;
+    result.push_str(str::from_utf8(&sigs)?);
+    Ok(result)
 }
 
-/// This function takes a password and an userid as strings, returns a tuple of public and private
-/// key and the fingerprint in hex. Remember to save the keys for future use.
-#[pyfunction]
-#[text_signature = "(password, userid, cipher)"]
-fn create_newkey(
-    password: String,
-    userid: String,
-    cipher: String,
-) -> PyResult<(String, String, String)> {
-    // Default we create RSA4k keys
-    let mut ciphervalue = CipherSuite::RSA4k;
-    if cipher == String::from("RSA2k") {
-        ciphervalue = CipherSuite::RSA4k;
-    } else if cipher == String::from("Cv25519") {
-        ciphervalue = CipherSuite::Cv25519;
-    }
-    let (cert, _) = CertBuilder::new()
-        .add_storage_encryption_subkey()
-        .add_signing_subkey()
-        .set_cipher_suite(ciphervalue)
-        .set_password(Some(openpgp::crypto::Password::from(password)))
-        .add_userid(userid)
-        .generate()
-        .unwrap();
-    let mut buf = Vec::new();
-    let mut buffer = Vec::new();
-
-    let mut writer = Writer::new(&mut buf, Kind::SecretKey).unwrap();
-    cert.as_tsk().serialize(&mut buffer).unwrap();
-    writer.write_all(&buffer).unwrap();
-    writer.finalize().unwrap();
-    let armored = cert.armored().to_vec().unwrap();
-    Ok((
-        String::from_utf8(armored).unwrap(),
-        String::from_utf8(buf).unwrap(),
-        cert.fingerprint().to_hex(),
-    ))
-}
+/// Splits the cleartext signed message into the hash algorithms from the Hash header, the
+/// dash-unescaped lines of the text, and the armored signature.
+fn parse_cleartext(data: &str) -> PyResult<(Vec<String>, Vec<String>, String)> {
+    let malformed = |msg: &str| {
+        MalformedMessageError::new_err(format!("Malformed cleartext signed message: {}", msg))
+    };
+    let mut lines = data.split('\n').map(|line| line.trim_end_matches('\r'));
+    // Anything before the header is ignored
+    if !lines
+        .by_ref()
+        .any(|line| line == "-----BEGIN PGP SIGNED MESSAGE-----")
+    {
+        return Err(malformed("missing the header."));
+    }
 
-/// This function takes a list of public key paths, and encrypts the given data in bytes to an output
-/// file. You can also pass boolen flag armor for armored output.
-#[pyfunction]
-#[text_signature = "(publickeys, data, output, armor=False)"]
-fn encrypt_bytes_to_file(
-    publickeys: Vec<String>,
-    data: Vec<u8>,
-    output: Vec<u8>,
-    armor: Option<bool>,
-) -> PyResult<bool> {
-    let mut certs = Vec::new();
-    for fpath in publickeys {
-        if !std::fs::metadata(fpath.clone()).is_ok() {
-            return Err(PyFileNotFoundError::new_err(format!(
-                "{} is not found.",
-                fpath
-            )));
+    let mut hashes = Vec::new();
+    for line in lines.by_ref() {
+        if line.trim().is_empty() {
+            break;
+        }
+        match line.strip_prefix("Hash:") {
+            Some(values) => hashes.extend(values.split(',').map(|v| v.trim().to_string())),
+            None => return Err(malformed("unknown armor header.")),
         }
-        certs.push(openpgp::Cert::from_file(&fpath).unwrap());
     }
-    let mode = KeyFlags::default().set_storage_encryption(true);
 
-    let p = &P::new();
-    let recipients = certs.iter().flat_map(|cert| {
-        cert.keys()
-            .with_policy(p, None)
-            .alive()
-            .revoked(false)
-            .key_flags(&mode)
-    });
-    let mut outfile = File::create(str::from_utf8(&output[..]).unwrap()).unwrap();
-    // TODO: Find better ways to write this code
-    match armor {
-        // For armored output file.
-        Some(true) => {
-            let mut sink = armor::Writer::new(&mut outfile, armor::Kind::Message).unwrap();
-            // Stream an OpenPGP message.
-            let message = Message::new(&mut sink);
-
-            // We want to encrypt a literal data packet.
-            let encryptor = Encryptor::for_recipients(message, recipients)
-                .build()
-                .expect("Failed to create encryptor");
-
-            let mut literal_writer = LiteralWriter::new(encryptor)
-                .build()
-                .expect("Failed to create literal writer");
-
-            // Copy data to our writer stack to encrypt the data.
-            literal_writer.write_all(&data).unwrap();
-
-            // Finally, finalize the OpenPGP message by tearing down the
-            // writer stack.
-            literal_writer.finalize().unwrap();
-
-            // Finalize the armor writer.
-            sink.finalize().expect("Failed to write data");
+    let mut text = Vec::new();
+    let mut signature = String::new();
+    for line in lines.by_ref() {
+        if line.starts_with("-----BEGIN PGP SIGNATURE-----") {
+            signature.push_str(line);
+            signature.push('\n');
+            break;
         }
-        _ => {
-            let message = Message::new(&mut outfile);
+        text.push(line.strip_prefix("- ").unwrap_or(line).to_string());
+    }
+    if signature.is_empty() {
+        return Err(malformed("missing the signature."));
+    }
+    for line in lines {
+        signature.push_str(line);
+        signature.push('\n');
+    }
+    Ok((hashes, text, signature))
+}
 
-            // We want to encrypt a literal data packet.
-            let encryptor = Encryptor::for_recipients(message, recipients)
-                .build()
-                .expect("Failed to create encryptor");
+/// Verifies the cleartext signed message, returns the text and the VerificationResult.
+fn verify_cleartext_internal(
+    cert: &openpgp::Cert,
+    data: &[u8],
+) -> PyResult<(String, VerificationResult)> {
+    let (hashes, lines, signature) = parse_cleartext(str::from_utf8(data)?)?;
+    let lines: Vec<&str> = lines.iter().map(|line| line.as_str()).collect();
+    let canonical = cleartext_canonical(&lines);
+
+    // The signatures must use one of the hash algorithms from the Hash header
+    if !hashes.is_empty() {
+        let pile = openpgp::PacketPile::from_bytes(signature.as_bytes())
+            .map_err(|e| MalformedMessageError::new_err(e.to_string()))?;
+        for packet in pile.descendants() {
+            if let openpgp::Packet::Signature(sig) = packet {
+                if !hashes.contains(&sig.hash_algo().to_string()) {
+                    return Err(MalformedMessageError::new_err(format!(
+                        "The signature uses {}, which is not in the Hash header.",
+                        sig.hash_algo()
+                    )));
+                }
+            }
+        }
+    }
 
-            let mut literal_writer = LiteralWriter::new(encryptor)
-                .build()
-                .expect("Failed to create literal writer");
+    let p = &P::new();
+    let mut v = get_detached_verifier(p, vec![cert.clone()], signature.as_bytes())?;
+    v.verify_bytes(canonical.as_bytes()).map_err(to_pyerr)?;
+    Ok((lines.join("\n"), v.into_helper().into()))
+}
 
-            // Copy data to our writer stack to encrypt the data.
-            literal_writer.write_all(&data).unwrap();
+/// The compression for the encryption, either the name of the algorithm, or a tuple of the name
+/// and the level (0 to 9).
+struct Compression(String, Option<u8>);
 
-            // Finally, finalize the OpenPGP message by tearing down the
-            // writer stack.
-            literal_writer.finalize().unwrap();
+impl<'source> FromPyObject<'source> for Compression {
+    fn extract(obj: &'source PyAny) -> PyResult<Self> {
+        if let Ok(name) = obj.extract::<String>() {
+            return Ok(Compression(name, None));
         }
+        let (name, level): (String, u8) = obj.extract()?;
+        Ok(Compression(name, Some(level)))
     }
+}
 
-    Ok(true)
+/// The algorithms to use for the encryption, the defaults of sequoia are used for None.
+struct EncryptionOptions {
+    symmetric: Option<SymmetricAlgorithm>,
+    compression: Option<(CompressionAlgorithm, Option<CompressionLevel>)>,
+    aead: Option<AEADAlgorithm>,
 }
 
-/// This function takes a list of public key paths, and encrypts the given filepath to an output
-/// file. You can also pass boolen flag armor for armored output.
-#[pyfunction]
-#[text_signature = "(publickeys, filepath, output, armor=False)"]
-fn encrypt_file_internal(
-    publickeys: Vec<String>,
+impl EncryptionOptions {
+    /// Creates the options from the names of the algorithms. Without an explicit cipher, the
+    /// preferred symmetric algorithm of the recipients is used, if all of them have one in
+    /// common. Raises `PolicyRejectedError` if the policy does not allow the chosen algorithm,
+    /// and `CryptoError` for AEAD if any of the recipients does not advertise the AEAD feature.
+    fn new(
+        p: &dyn Policy,
+        certs: &[openpgp::Cert],
+        cipher: Option<String>,
+        compression: Option<Compression>,
+        aead: Option<String>,
+    ) -> PyResult<Self> {
+        let symmetric = match cipher {
+            Some(name) => {
+                let algo = get_symmetric_algo(&name)?;
+                p.symmetric_algorithm(algo).map_err(|e| {
+                    PolicyRejectedError::new_err(format!("{} is not allowed: {}", name, e))
+                })?;
+                Some(algo)
+            }
+            None => preferred_symmetric_algo(p, certs),
+        };
+        let aead = match aead {
+            Some(name) => {
+                let algo = match name.as_str() {
+                    "eax" => AEADAlgorithm::EAX,
+                    "ocb" => AEADAlgorithm::OCB,
+                    _ => {
+                        return Err(PyValueError::new_err(format!(
+                            "{} is not a known AEAD mode, use eax or ocb.",
+                            name
+                        )))
+                    }
+                };
+                if !algo.is_supported() {
+                    return Err(CryptoError::new_err(format!("{} is not supported.", name)));
+                }
+                p.aead_algorithm(algo).map_err(|e| {
+                    PolicyRejectedError::new_err(format!("{} is not allowed: {}", name, e))
+                })?;
+                // The recipients without AEAD support (like GnuPG) could not read the message
+                for cert in certs {
+                    let supported = cert
+                        .with_policy(p, None)
+                        .ok()
+                        .and_then(|vc| vc.features())
+                        .map_or(false, |features| features.supports_aead());
+                    if !supported {
+                        return Err(CryptoError::new_err(format!(
+                            "{} does not support AEAD.",
+                            cert.fingerprint().to_hex()
+                        )));
+                    }
+                }
+                Some(algo)
+            }
+            None => None,
+        };
+        let compression = match compression {
+            Some(Compression(name, level)) => {
+                let algo = match name.as_str() {
+                    "none" => None,
+                    "zip" => Some(CompressionAlgorithm::Zip),
+                    "zlib" => Some(CompressionAlgorithm::Zlib),
+                    "bzip2" => Some(CompressionAlgorithm::BZip2),
+                    _ => {
+                        return Err(PyValueError::new_err(format!(
+                            "{} is not a known compression, use none, zip, zlib or bzip2.",
+                            name
+                        )))
+                    }
+                };
+                let level = match level {
+                    Some(level) => Some(
+                        CompressionLevel::new(level)
+                            .map_err(|e| PyValueError::new_err(e.to_string()))?,
+                    ),
+                    None => None,
+                };
+                algo.map(|algo| (algo, level))
+            }
+            None => None,
+        };
+        Ok(EncryptionOptions {
+            symmetric,
+            compression,
+            aead,
+        })
+    }
+}
+
+/// Returns the symmetric algorithm for the given name.
+fn get_symmetric_algo(name: &str) -> PyResult<SymmetricAlgorithm> {
+    let algo = match name {
+        "aes128" => SymmetricAlgorithm::AES128,
+        "aes192" => SymmetricAlgorithm::AES192,
+        "aes256" => SymmetricAlgorithm::AES256,
+        "twofish" => SymmetricAlgorithm::Twofish,
+        "camellia128" => SymmetricAlgorithm::Camellia128,
+        "camellia192" => SymmetricAlgorithm::Camellia192,
+        "camellia256" => SymmetricAlgorithm::Camellia256,
+        "blowfish" => SymmetricAlgorithm::Blowfish,
+        "cast5" => SymmetricAlgorithm::CAST5,
+        "3des" => SymmetricAlgorithm::TripleDES,
+        "idea" => SymmetricAlgorithm::IDEA,
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "{} is not a known cipher, use aes128, aes192, aes256, twofish, camellia128, \
+                 camellia192, camellia256, blowfish, cast5, 3des or idea.",
+                name
+            )))
+        }
+    };
+    if !algo.is_supported() {
+        return Err(CryptoError::new_err(format!("{} is not supported.", name)));
+    }
+    Ok(algo)
+}
+
+/// Returns the first preferred symmetric algorithm of the first Cert, which is also preferred by
+/// all the other Certs and allowed by the policy.
+fn preferred_symmetric_algo(p: &dyn Policy, certs: &[openpgp::Cert]) -> Option<SymmetricAlgorithm> {
+    let mut preferences = Vec::new();
+    for cert in certs {
+        let vc = cert.with_policy(p, None).ok()?;
+        preferences.push(vc.preferred_symmetric_algorithms()?.to_vec());
+    }
+    let (first, rest) = preferences.split_first()?;
+    first
+        .iter()
+        .find(|algo| {
+            algo.is_supported()
+                && p.symmetric_algorithm(**algo).is_ok()
+                && rest.iter().all(|prefs| prefs.contains(algo))
+        })
+        .cloned()
+}
+
+/// Returns the encryption capable (sub)keys of the given Certs as recipients.
+fn get_recipients<'a>(
+    p: &'a dyn Policy,
+    certs: &'a [openpgp::Cert],
+    wildcard: bool,
+) -> PyResult<Vec<Recipient<'a>>> {
+    let mode = KeyFlags::default()
+        .set_storage_encryption(true)
+        .set_transport_encryption(true);
+    let mut recipients = Vec::new();
+    for cert in certs {
+        cert.with_policy(p, None).map_err(|e| {
+            PolicyRejectedError::new_err(format!(
+                "{} is not valid under the policy: {}",
+                cert.fingerprint().to_hex(),
+                e
+            ))
+        })?;
+        let before = recipients.len();
+        recipients.extend(
+            cert.keys()
+                .with_policy(p, None)
+                .alive()
+                .revoked(false)
+                .key_flags(&mode)
+                .map(|ka| {
+                    let mut recipient: Recipient = ka.into();
+                    // Hides the recipient, the wildcard KeyID is written in the PKESK
+                    if wildcard {
+                        recipient.set_keyid(openpgp::KeyID::wildcard());
+                    }
+                    recipient
+                }),
+        );
+        if recipients.len() == before {
+            return Err(CryptoError::new_err(format!(
+                "{} does not have any valid encryption subkey.",
+                cert.fingerprint().to_hex()
+            )));
+        }
+    }
+    Ok(recipients)
+}
+
+// Encrypts everything from input for the recipients/passwords and writes into the message.
+// If any signer is given, the data is signed inside of the encryption.
+fn encrypt_message(
+    message: Message,
+    recipients: Vec<Recipient>,
+    passwords: Vec<openpgp::crypto::Password>,
+    signers: Vec<KeyPair>,
+    input: &mut dyn io::Read,
+    options: &EncryptionOptions,
+) -> openpgp::Result<()> {
+    // We want to encrypt a literal data packet.
+    let mut encryptor = Encryptor::for_recipients(message, recipients).add_passwords(passwords);
+    if let Some(algo) = options.symmetric {
+        encryptor = encryptor.symmetric_algo(algo);
+    }
+    if let Some(algo) = options.aead {
+        encryptor = encryptor.aead_algo(algo);
+    }
+    let mut encryptor = encryptor.build()?;
+
+    // The data is compressed before the encryption, and the signatures are made inside
+    if let Some((algo, level)) = options.compression {
+        let mut compressor = Compressor::new(encryptor).algo(algo);
+        if let Some(level) = level {
+            compressor = compressor.level(level);
+        }
+        encryptor = compressor.build()?;
+    }
+
+    // The signer goes inside of the encryptor
+    let mut signers = signers.into_iter();
+    let message = match signers.next() {
+        Some(first) => {
+            let mut signer = Signer::new(encryptor, first);
+            for s in signers {
+                signer = signer.add_signer(s);
+            }
+            signer.build()?
+        }
+        None => encryptor,
+    };
+
+    let mut literal_writer = LiteralWriter::new(message).build()?;
+
+    // Copy data to our writer stack to encrypt the data.
+    io::copy(input, &mut literal_writer)?;
+
+    // Finally, finalize the OpenPGP message by tearing down the
+    // writer stack.
+    literal_writer.finalize()?;
+    Ok(())
+}
+
+/// Encrypts the input for the given recipients and passwords, and writes to the output, armored
+/// if asked.
+fn encrypt_internal(
+    recipients: Vec<Recipient>,
+    passwords: Vec<openpgp::crypto::Password>,
+    signers: Vec<KeyPair>,
+    input: &mut dyn io::Read,
+    output: &mut dyn io::Write,
+    armor: bool,
+    options: &EncryptionOptions,
+) -> PyResult<()> {
+    if armor {
+        let mut sink =
+            armor::Writer::new(output, armor::Kind::Message).map_err(|e| to_pyerr(e.into()))?;
+        // Stream an OpenPGP message.
+        encrypt_message(
+            Message::new(&mut sink),
+            recipients,
+            passwords,
+            signers,
+            input,
+            options,
+        )
+        .map_err(to_pyerr)?;
+        // Finalize the armor writer, the output can be a Python file object
+        sink.finalize().map_err(|e| to_pyerr(e.into()))?;
+    } else {
+        encrypt_message(
+            Message::new(output),
+            recipients,
+            passwords,
+            signers,
+            input,
+            options,
+        )
+        .map_err(to_pyerr)?;
+    }
+    Ok(())
+}
+
+/// Signs the input as a literal data packet with one-pass signatures (inline signature), and
+/// writes to the message.
+fn sign_message(
+    message: Message,
+    signers: Vec<KeyPair>,
+    input: &mut dyn io::Read,
+) -> openpgp::Result<()> {
+    let mut signers = signers.into_iter();
+    let first = signers
+        .next()
+        .ok_or_else(|| NoSigningKeyError::new_err("No signing key is present."))?;
+    let mut signer = Signer::new(message, first);
+    for s in signers {
+        signer = signer.add_signer(s);
+    }
+    let mut literal_writer = LiteralWriter::new(signer.build()?).build()?;
+    io::copy(input, &mut literal_writer)?;
+    literal_writer.finalize()?;
+    Ok(())
+}
+
+/// Signs the input with the given signers (inline signature), and writes to the output, armored
+/// if asked.
+fn sign_internal(
+    signers: Vec<KeyPair>,
+    input: &mut dyn io::Read,
+    output: &mut dyn io::Write,
+    armor: bool,
+) -> PyResult<()> {
+    if armor {
+        let mut sink = armor::Writer::new(output, armor::Kind::Message)?;
+        sign_message(Message::new(&mut sink), signers, input).map_err(to_pyerr)?;
+        sink.finalize()?;
+    } else {
+        sign_message(Message::new(output), signers, input).map_err(to_pyerr)?;
+    }
+    Ok(())
+}
+
+/// Verifies the inline signed message from the input using the given Certs, and writes the
+/// signed content to the output.
+fn verify_internal<R: io::Read>(
+    certs: Vec<openpgp::Cert>,
+    input: R,
+    output: &mut dyn io::Write,
+) -> PyResult<VerificationResult> {
+    let p = &P::new();
+    let mut verifier = VerifierBuilder::from_reader(input)
+        .map_err(|e| MalformedMessageError::new_err(e.to_string()))?
+        .with_policy(p, None, VHelper::new(certs))
+        .map_err(to_pyerr)?;
+    io::copy(&mut verifier, output)?;
+    Ok(verifier.into_helper().into())
+}
+
+/// Creates the DecryptionResult from the helper after the whole message is decrypted.
+fn get_decryption_result(
+    helper: Helper,
+    plaintext: PyObject,
+    output: Option<String>,
+) -> PyResult<DecryptionResult> {
+    let literal = helper.literal;
+    Ok(DecryptionResult {
+        plaintext,
+        output,
+        fingerprint: helper.decrypted_by.map(|fp| fp.to_hex()),
+        recipients: helper.recipients.iter().map(|id| id.to_hex()).collect(),
+        sym_algo: helper.session_key.map(|(algo, _)| format!("{:?}", algo)),
+        filename: literal
+            .as_ref()
+            .and_then(|l| l.filename())
+            .map(|name| String::from_utf8_lossy(name).into_owned()),
+        date: literal
+            .as_ref()
+            .and_then(|l| l.date())
+            .and_then(|date| date.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs()),
+        format: literal.as_ref().map(|l| format!("{:?}", l.format())),
+        verification: VerificationResult {
+            signatures: helper.signatures,
+        },
+    })
+}
+
+/// Creates the Decryptor for the input, this is where the session key gets decrypted.
+fn get_decryptor<'a, R: io::Read + 'a>(
+    p: &'a dyn Policy,
+    input: R,
+    helper: Helper,
+) -> PyResult<Decryptor<'a, Helper>> {
+    DecryptorBuilder::from_reader(input)
+        .map_err(|e| MalformedMessageError::new_err(e.to_string()))?
+        .with_policy(p, None, helper)
+        .map_err(to_pyerr)
+}
+
+#[pyfunction]
+#[text_signature = "(certpath)"]
+fn parse_cert_file(py: Python, certpath: String) -> PyResult<(PyObject, String, bool)> {
+    let cert = read_cert(&certpath)?;
+    let plist = PyList::empty(py);
+    for ua in cert.userids() {
+        plist.append(userid_dict(py, ua.userid())?)?;
+    }
+
+    Ok((plist.into(), cert.fingerprint().to_hex(), cert.is_tsk()))
+}
+
+/// Returns the different parts of the UID as a dictionary.
+fn userid_dict<'p>(py: Python<'p>, ua: &openpgp::packet::UserID) -> PyResult<&'p PyDict> {
+    let pd = PyDict::new(py);
+    pd.set_item("value", String::from_utf8_lossy(ua.value()))?;
+    // If we have a name part in the UID
+    match ua.name() {
+        Ok(value) => match value {
+            Some(name) => {
+                pd.set_item("name", name)?;
+            }
+            _ => (),
+        },
+        Err(_) => (),
+    }
+    // If we have a comment part in the UID
+    match ua.comment() {
+        Ok(value) => match value {
+            Some(comment) => {
+                pd.set_item("comment", comment)?;
+            }
+            _ => (),
+        },
+        Err(_) => (),
+    }
+    // If we have a email part in the UID
+    match ua.email() {
+        Ok(value) => match value {
+            Some(email) => {
+                pd.set_item("email", email)?;
+            }
+            _ => (),
+        },
+        Err(_) => (),
+    }
+    // If we have a URI part in the UID
+    match ua.uri() {
+        Ok(value) => match value {
+            Some(uri) => {
+                pd.set_item("uri", uri)?;
+            }
+            _ => (),
+        },
+        Err(_) => (),
+    }
+    Ok(pd)
+}
+
+/// Returns the policy by the name, "standard" (the default) or "null".
+fn get_policy(name: Option<String>) -> PyResult<Box<dyn Policy>> {
+    match name.as_deref() {
+        None | Some("standard") => Ok(Box::new(P::new())),
+        Some("null") => Ok(Box::new(NP::new())),
+        Some(other) => Err(PyValueError::new_err(format!(
+            "{} is not a known policy, use standard or null.",
+            other
+        ))),
+    }
+}
+
+/// Converts the time to seconds since the epoch.
+fn to_timestamp(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Returns the details of the (sub)key which do not depend on the policy.
+fn key_dict<'p, R: key::KeyRole>(
+    py: Python<'p>,
+    key: &openpgp::packet::Key<key::PublicParts, R>,
+) -> PyResult<&'p PyDict> {
+    let pd = PyDict::new(py);
+    pd.set_item("fingerprint", key.fingerprint().to_hex())?;
+    pd.set_item("keyid", key.keyid().to_hex())?;
+    pd.set_item("algorithm", format!("{:?}", key.pk_algo()))?;
+    pd.set_item("bits", key.mpis().bits())?;
+    pd.set_item("creation_time", to_timestamp(key.creation_time()))?;
+    pd.set_item("has_secret", key.has_secret())?;
+    pd.set_item(
+        "secret_encrypted",
+        key.optional_secret()
+            .map(|secret| secret.is_encrypted())
+            .unwrap_or(false),
+    )?;
+    Ok(pd)
+}
+
+/// Adds the key flags, expiration time and revocation status of the valid (sub)key.
+fn add_key_validity<'a>(
+    pd: &PyDict,
+    ka: &ValidErasedKeyAmalgamation<'a, key::PublicParts>,
+) -> PyResult<()> {
+    let flags = ka.key_flags().unwrap_or_default();
+    pd.set_item("valid", true)?;
+    pd.set_item("sign", flags.for_signing())?;
+    pd.set_item("certify", flags.for_certification())?;
+    pd.set_item("encrypt_storage", flags.for_storage_encryption())?;
+    pd.set_item("encrypt_transport", flags.for_transport_encryption())?;
+    pd.set_item("authenticate", flags.for_authentication())?;
+    pd.set_item(
+        "expiration_time",
+        ka.key_expiration_time().map(to_timestamp),
+    )?;
+    pd.set_item(
+        "revoked",
+        matches!(ka.revocation_status(), RevocationStatus::Revoked(_)),
+    )?;
+    Ok(())
+}
+
+/// Returns all the details of the Cert as a dictionary, the validity, flags, expiration and
+/// revocation of the keys and the UIDs are evaluated under the given policy.
+fn cert_details(py: Python, cert: &openpgp::Cert, p: &dyn Policy) -> PyResult<PyObject> {
+    let details = key_dict(py, cert.primary_key().key())?;
+    details.set_item("is_secret", cert.is_tsk())?;
+    match cert.with_policy(p, None) {
+        Ok(vc) => {
+            add_key_validity(details, &vc.primary_key().into())?;
+            details.set_item(
+                "revoked",
+                matches!(vc.revocation_status(), RevocationStatus::Revoked(_)),
+            )?;
+        }
+        Err(e) => {
+            details.set_item("valid", false)?;
+            details.set_item("error", e.to_string())?;
+        }
+    }
+
+    let subkeys = PyList::empty(py);
+    for ka in cert.keys().subkeys() {
+        let pd = key_dict(py, ka.key())?;
+        match ka.clone().with_policy(p, None) {
+            Ok(vka) => add_key_validity(pd, &vka.into())?,
+            Err(e) => {
+                pd.set_item("valid", false)?;
+                pd.set_item("error", e.to_string())?;
+            }
+        }
+        subkeys.append(pd)?;
+    }
+    details.set_item("subkeys", subkeys)?;
+
+    let primary_uid = cert
+        .with_policy(p, None)
+        .and_then(|vc| vc.primary_userid().map(|ua| ua.userid().clone()))
+        .ok();
+    let uids = PyList::empty(py);
+    for ua in cert.userids() {
+        let pd = userid_dict(py, ua.userid())?;
+        pd.set_item("primary", primary_uid.as_ref() == Some(ua.userid()))?;
+        pd.set_item("certifications", ua.certifications().len())?;
+        match ua.clone().with_policy(p, None) {
+            Ok(vua) => {
+                let sig = vua.binding_signature();
+                pd.set_item("valid", true)?;
+                pd.set_item(
+                    "creation_time",
+                    sig.signature_creation_time().map(to_timestamp),
+                )?;
+                pd.set_item(
+                    "revoked",
+                    matches!(vua.revocation_status(), RevocationStatus::Revoked(_)),
+                )?;
+            }
+            Err(e) => {
+                pd.set_item("valid", false)?;
+                pd.set_item("error", e.to_string())?;
+            }
+        }
+        uids.append(pd)?;
+    }
+    details.set_item("uids", uids)?;
+    Ok(details.into())
+}
+
+/// Returns the CipherSuite for the given name.
+fn get_cipher_suite(cipher: &str) -> PyResult<CipherSuite> {
+    match cipher {
+        "RSA2k" => Ok(CipherSuite::RSA2k),
+        "RSA3k" => Ok(CipherSuite::RSA3k),
+        "RSA4k" => Ok(CipherSuite::RSA4k),
+        "Cv25519" => Ok(CipherSuite::Cv25519),
+        "P256" => Ok(CipherSuite::P256),
+        "P384" => Ok(CipherSuite::P384),
+        "P521" => Ok(CipherSuite::P521),
+        _ => Err(PyValueError::new_err(format!(
+            "{} is not a known cipher suite.",
+            cipher
+        ))),
+    }
+}
+
+/// Returns the KeyFlags for the encryption subkey, "storage", "transport", "both" or "none".
+fn get_encryption_flags(encryption: &str) -> PyResult<Option<KeyFlags>> {
+    match encryption {
+        "storage" => Ok(Some(KeyFlags::default().set_storage_encryption(true))),
+        "transport" => Ok(Some(KeyFlags::default().set_transport_encryption(true))),
+        "both" => Ok(Some(
+            KeyFlags::default()
+                .set_storage_encryption(true)
+                .set_transport_encryption(true),
+        )),
+        "none" => Ok(None),
+        _ => Err(PyValueError::new_err(format!(
+            "{} is not a known encryption subkey type, use storage, transport, both or none.",
+            encryption
+        ))),
+    }
+}
+
+/// This function takes a password and an userid (or a list of userids), returns a tuple of public
+/// and private key, the fingerprint in hex and the armored revocation certificate. Remember to
+/// save the keys and the revocation certificate for future use.
+///
+/// If the password is None, the secret key is not protected. The expiration values are the
+/// validity periods in seconds from the creation time, and the encryption subkey can be
+/// "storage", "transport", "both" or "none".
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+#[text_signature = "(password, userid, cipher=\"RSA4k\", creation_time=None, expiration=None, subkeys_expiration=None, signing=True, encryption=\"storage\", authentication=False)"]
+fn create_newkey(
+    py: Python,
+    password: Option<String>,
+    userid: &PyAny,
+    cipher: Option<String>,
+    creation_time: Option<u64>,
+    expiration: Option<u64>,
+    subkeys_expiration: Option<u64>,
+    signing: Option<bool>,
+    encryption: Option<String>,
+    authentication: Option<bool>,
+) -> PyResult<(String, String, String, String)> {
+    // Default we create RSA4k keys
+    let ciphervalue = get_cipher_suite(&cipher.unwrap_or_else(|| String::from("RSA4k")))?;
+    let userids: Vec<String> = match userid.extract::<String>() {
+        Ok(value) => vec![value],
+        Err(_) => userid.extract()?,
+    };
+    // By default we create a storage encryption subkey
+    let encryption_flags =
+        get_encryption_flags(&encryption.unwrap_or_else(|| String::from("storage")))?;
+    // The expiration times are absolute, counted from the creation time of the key
+    let creation = creation_time
+        .map(|time| UNIX_EPOCH + Duration::from_secs(time))
+        .unwrap_or_else(SystemTime::now);
+    let subkeys_expiration = subkeys_expiration.map(|secs| creation + Duration::from_secs(secs));
+
+    let mut builder = CertBuilder::new()
+        .set_cipher_suite(ciphervalue)
+        .set_password(password.map(openpgp::crypto::Password::from))
+        .set_creation_time(creation)
+        .set_expiration_time(expiration.map(|secs| creation + Duration::from_secs(secs)));
+    for uid in userids {
+        builder = builder.add_userid(uid);
+    }
+    if let Some(flags) = encryption_flags {
+        builder = builder.add_subkey(flags, subkeys_expiration, None);
+    }
+    if signing.unwrap_or(true) {
+        builder = builder.add_subkey(
+            KeyFlags::default().set_signing(true),
+            subkeys_expiration,
+            None,
+        );
+    }
+    if authentication.unwrap_or(false) {
+        builder = builder.add_subkey(
+            KeyFlags::default().set_authentication(true),
+            subkeys_expiration,
+            None,
+        );
+    }
+    // Generating RSA keys takes time, other Python threads can run meanwhile
+    let (cert, revocation) = py
+        .allow_threads(move || builder.generate())
+        .map_err(to_pyerr)?;
+    Ok((
+        armored_public(&cert)?,
+        armored_secret(&cert)?,
+        cert.fingerprint().to_hex(),
+        armored_signature(revocation)?,
+    ))
+}
+
+/// Returns the signature (like a revocation certificate) as armored string.
+fn armored_signature(sig: openpgp::packet::Signature) -> PyResult<String> {
+    let mut buf = Vec::new();
+    let mut writer = Writer::new(&mut buf, Kind::Signature)?;
+    openpgp::Packet::from(sig)
+        .serialize(&mut writer)
+        .map_err(to_pyerr)?;
+    writer.finalize()?;
+    Ok(String::from_utf8(buf)?)
+}
+
+/// Returns the ReasonForRevocation for the given name.
+fn get_revocation_reason(reason: &str) -> PyResult<ReasonForRevocation> {
+    match reason {
+        "unspecified" => Ok(ReasonForRevocation::Unspecified),
+        "compromised" => Ok(ReasonForRevocation::KeyCompromised),
+        "superseded" => Ok(ReasonForRevocation::KeySuperseded),
+        "retired" => Ok(ReasonForRevocation::KeyRetired),
+        _ => Err(PyValueError::new_err(format!(
+            "{} is not a known reason, use unspecified, compromised, superseded or retired.",
+            reason
+        ))),
+    }
+}
+
+/// Returns the KeyPair of the primary key, to create the certifications and revocations.
+fn get_primary_keypair(cert: &openpgp::Cert, password: &str) -> PyResult<KeyPair> {
+    let key = cert
+        .primary_key()
+        .key()
+        .clone()
+        .parts_into_secret()
+        .map_err(|_| {
+            CryptoError::new_err(format!(
+                "{} does not have the primary secret key.",
+                cert.fingerprint().to_hex()
+            ))
+        })?;
+    get_keypair(&key.role_into_unspecified(), password)
+}
+
+/// This function takes a secret key (Cert object or path), the current password and the new
+/// password, returns the armored secret key with the primary key and all the subkeys encrypted
+/// with the new password. If the new password is None, the secret key is not protected anymore.
+#[pyfunction]
+#[text_signature = "(secretkey, password, newpassword)"]
+fn change_password(
+    secretkey: CertOrPath,
+    password: String,
+    newpassword: Option<String>,
+) -> PyResult<String> {
+    let cert = secretkey.0;
+    if !cert.is_tsk() {
+        return Err(CryptoError::new_err(format!(
+            "{} is not a secret key.",
+            cert.fingerprint().to_hex()
+        )));
+    }
+    let newpassword = newpassword.map(openpgp::crypto::Password::from);
+    let mut packets: Vec<openpgp::Packet> = Vec::new();
+    for packet in cert.into_packets() {
+        // The keys without any secret material are kept as they are
+        let packet = match packet {
+            openpgp::Packet::SecretKey(key) => {
+                reencrypt_secret(key, &password, &newpassword)?.into()
+            }
+            openpgp::Packet::SecretSubkey(key) => {
+                reencrypt_secret(key, &password, &newpassword)?.into()
+            }
+            packet => packet,
+        };
+        packets.push(packet);
+    }
+    let cert = openpgp::Cert::from_packets(packets.into_iter()).map_err(to_pyerr)?;
+    armored_secret(&cert)
+}
+
+/// This function takes a secret key (Cert object or path) and the password, returns an armored
+/// revocation certificate for the key. The reason can be "unspecified", "compromised",
+/// "superseded" or "retired", with an optional human readable message.
+#[pyfunction]
+#[text_signature = "(secretkey, password, reason=\"unspecified\", message=\"\")"]
+fn revoke_cert(
+    secretkey: CertOrPath,
+    password: String,
+    reason: Option<String>,
+    message: Option<String>,
+) -> PyResult<String> {
+    let cert = secretkey.0;
+    let code = get_revocation_reason(&reason.unwrap_or_else(|| String::from("unspecified")))?;
+    let mut keypair = get_primary_keypair(&cert, &password)?;
+    let sig = CertRevocationBuilder::new()
+        .set_reason_for_revocation(code, message.unwrap_or_default().as_bytes())
+        .map_err(to_pyerr)?
+        .build(&mut keypair, &cert, None)
+        .map_err(to_pyerr)?;
+    armored_signature(sig)
+}
+
+/// This function takes a key (Cert object or path) and a revocation certificate, returns the
+/// armored key with the revocation merged in. A secret key stays a secret key.
+#[pyfunction]
+#[text_signature = "(key, revocation)"]
+fn apply_revocation(key: CertOrPath, revocation: Vec<u8>) -> PyResult<String> {
+    let pile = openpgp::PacketPile::from_bytes(&revocation).map_err(|e| {
+        MalformedMessageError::new_err(format!("Can not parse the revocation certificate: {}", e))
+    })?;
+    let packets: Vec<openpgp::Packet> = pile
+        .into_children()
+        .filter(|packet| matches!(packet, openpgp::Packet::Signature(_)))
+        .collect();
+    let cert = key.0.merge_packets(packets).map_err(to_pyerr)?;
+    // Invalid signatures are dropped while merging, so check that the key is revoked now
+    if !matches!(
+        cert.revocation_status(&P::new(), None),
+        RevocationStatus::Revoked(_)
+    ) {
+        return Err(CryptoError::new_err(format!(
+            "The revocation certificate is not valid for {}.",
+            cert.fingerprint().to_hex()
+        )));
+    }
+    if cert.is_tsk() {
+        armored_secret(&cert)
+    } else {
+        armored_public(&cert)
+    }
+}
+
+/// Returns the UserID of the Cert with the given value.
+fn find_userid(cert: &openpgp::Cert, uid: &str) -> PyResult<openpgp::packet::UserID> {
+    cert.userids()
+        .map(|ua| ua.userid())
+        .find(|userid| userid.value() == uid.as_bytes())
+        .cloned()
+        .ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "{} is not an user ID of {}.",
+                uid,
+                cert.fingerprint().to_hex()
+            ))
+        })
+}
+
+/// Returns a SignatureBuilder based on the binding signature of the primary user ID, so that the
+/// new self signatures keep the key flags, preferences and expiration of the primary key.
+fn primary_userid_template(cert: &openpgp::Cert, p: &dyn Policy) -> PyResult<SignatureBuilder> {
+    let template = cert
+        .with_policy(p, None)
+        .and_then(|vc| {
+            vc.primary_userid()
+                .map(|ua| SignatureBuilder::from(ua.binding_signature().clone()))
+        })
+        .unwrap_or_else(|_| SignatureBuilder::new(SignatureType::PositiveCertification));
+    template
+        .set_signature_creation_time(SystemTime::now())
+        .map_err(to_pyerr)
+}
+
+/// Returns the public and the secret key as armored strings.
+fn armored_keys(cert: &openpgp::Cert) -> PyResult<(String, String)> {
+    Ok((armored_public(cert)?, armored_secret(cert)?))
+}
+
+/// This function takes a secret key (Cert object or path), the password and a new user ID, returns
+/// a tuple of the armored public and secret key with the new self signed user ID.
+#[pyfunction]
+#[text_signature = "(secretkey, password, uid)"]
+fn add_userid(secretkey: CertOrPath, password: String, uid: String) -> PyResult<(String, String)> {
+    let cert = secretkey.0;
+    let mut keypair = get_primary_keypair(&cert, &password)?;
+    let userid = openpgp::packet::UserID::from(uid);
+    let template = primary_userid_template(&cert, &P::new())?
+        .set_primary_userid(false)
+        .map_err(to_pyerr)?;
+    let sig = userid
+        .bind(&mut keypair, &cert, template)
+        .map_err(to_pyerr)?;
+    let cert = cert
+        .merge_packets(vec![userid.into(), sig.into()])
+        .map_err(to_pyerr)?;
+    armored_keys(&cert)
+}
+
+/// This function takes a secret key (Cert object or path), the password and an user ID of the
+/// key, returns a tuple of the armored public and secret key with the user ID revoked. The reason
+/// can be "unspecified" or "retired", with an optional human readable message.
+#[pyfunction]
+#[text_signature = "(secretkey, password, uid, reason=\"unspecified\", message=\"\")"]
+fn revoke_userid(
+    secretkey: CertOrPath,
+    password: String,
+    uid: String,
+    reason: Option<String>,
+    message: Option<String>,
+) -> PyResult<(String, String)> {
+    let cert = secretkey.0;
+    let code = match reason.as_deref().unwrap_or("unspecified") {
+        "unspecified" => ReasonForRevocation::Unspecified,
+        "retired" => ReasonForRevocation::UIDRetired,
+        value => {
+            return Err(PyValueError::new_err(format!(
+                "{} is not a known reason for an user ID, use unspecified or retired.",
+                value
+            )))
+        }
+    };
+    let userid = find_userid(&cert, &uid)?;
+    let mut keypair = get_primary_keypair(&cert, &password)?;
+    let sig = UserIDRevocationBuilder::new()
+        .set_reason_for_revocation(code, message.unwrap_or_default().as_bytes())
+        .map_err(to_pyerr)?
+        .build(&mut keypair, &cert, &userid, None)
+        .map_err(to_pyerr)?;
+    let cert = cert.merge_packets(vec![sig.into()]).map_err(to_pyerr)?;
+    armored_keys(&cert)
+}
+
+/// This function takes a secret key (Cert object or path), the password and an user ID of the
+/// key, returns a tuple of the armored public and secret key with the user ID as the primary one.
+#[pyfunction]
+#[text_signature = "(secretkey, password, uid)"]
+fn set_primary_userid(
+    secretkey: CertOrPath,
+    password: String,
+    uid: String,
+) -> PyResult<(String, String)> {
+    let p = P::new();
+    let cert = secretkey.0;
+    let userid = find_userid(&cert, &uid)?;
+    let mut keypair = get_primary_keypair(&cert, &password)?;
+
+    let mut packets: Vec<openpgp::Packet> = Vec::new();
+    let template = primary_userid_template(&cert, &p)?
+        .set_primary_userid(true)
+        .map_err(to_pyerr)?;
+    packets.push(
+        userid
+            .bind(&mut keypair, &cert, template)
+            .map_err(to_pyerr)?
+            .into(),
+    );
+    // Any other user ID marked as primary must lose the flag
+    let vc = cert.with_policy(&p, None).map_err(to_pyerr)?;
+    for ua in vc.userids() {
+        let sig = ua.binding_signature();
+        if ua.userid() == &userid || !sig.primary_userid().unwrap_or(false) {
+            continue;
+        }
+        let template = SignatureBuilder::from(sig.clone())
+            .set_signature_creation_time(SystemTime::now())
+            .and_then(|builder| builder.set_primary_userid(false))
+            .map_err(to_pyerr)?;
+        packets.push(
+            ua.userid()
+                .bind(&mut keypair, &cert, template)
+                .map_err(to_pyerr)?
+                .into(),
+        );
+    }
+    let cert = cert.merge_packets(packets).map_err(to_pyerr)?;
+    armored_keys(&cert)
+}
+
+/// Generates a new subkey of the given cipher suite name for the given key flags.
+fn generate_subkey(
+    cipher: &str,
+    flags: &KeyFlags,
+) -> PyResult<openpgp::packet::Key<key::SecretParts, key::SubordinateRole>> {
+    let for_signing =
+        flags.for_signing() || flags.for_certification() || flags.for_authentication();
+    let key = match cipher {
+        "RSA2k" => Key4::generate_rsa(2048),
+        "RSA3k" => Key4::generate_rsa(3072),
+        "RSA4k" => Key4::generate_rsa(4096),
+        "Cv25519" if for_signing => Key4::generate_ecc(true, Curve::Ed25519),
+        "Cv25519" => Key4::generate_ecc(false, Curve::Cv25519),
+        "P256" => Key4::generate_ecc(for_signing, Curve::NistP256),
+        "P384" => Key4::generate_ecc(for_signing, Curve::NistP384),
+        "P521" => Key4::generate_ecc(for_signing, Curve::NistP521),
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "{} is not a known cipher suite.",
+                cipher
+            )))
+        }
+    };
+    Ok(key.map_err(to_pyerr)?.into())
+}
+
+/// Returns the subkey of the Cert with the given fingerprint.
+fn find_subkey<'a>(
+    cert: &'a openpgp::Cert,
+    fingerprint: &str,
+) -> PyResult<SubordinateKeyAmalgamation<'a, key::PublicParts>> {
+    let fingerprint = fingerprint.replace(" ", "").to_uppercase();
+    cert.keys()
+        .subkeys()
+        .find(|ka| ka.key().fingerprint().to_hex() == fingerprint)
+        .ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "{} is not a subkey of {}.",
+                fingerprint,
+                cert.fingerprint().to_hex()
+            ))
+        })
+}
+
+/// Returns the validity period of the key for the given expiration time in seconds since epoch.
+fn get_validity_period<R: key::KeyRole>(
+    key: &openpgp::packet::Key<key::PublicParts, R>,
+    when: Option<u64>,
+) -> PyResult<Option<Duration>> {
+    match when {
+        Some(when) => (UNIX_EPOCH + Duration::from_secs(when))
+            .duration_since(key.creation_time())
+            .map(Some)
+            .map_err(|_| {
+                PyValueError::new_err(format!(
+                    "The expiration time is before the creation time of {}.",
+                    key.fingerprint().to_hex()
+                ))
+            }),
+        None => Ok(None),
+    }
+}
+
+/// This function takes a secret key (Cert object or path) and the password, adds a new subkey
+/// with the given cipher suite and usage, and returns a tuple of the armored public and secret
+/// key. The expiration is the validity period of the subkey in seconds. The secret of the new
+/// subkey is encrypted with the same password as the primary key.
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+#[text_signature = "(secretkey, password, cipher=\"RSA4k\", expiration=None, signing=False, encryption=None, authentication=False)"]
+fn add_subkey(
+    py: Python,
+    secretkey: CertOrPath,
+    password: String,
+    cipher: Option<String>,
+    expiration: Option<u64>,
+    signing: Option<bool>,
+    encryption: Option<String>,
+    authentication: Option<bool>,
+) -> PyResult<(String, String)> {
+    let cert = secretkey.0;
+    let cipher = cipher.unwrap_or_else(|| String::from("RSA4k"));
+    let signing = signing.unwrap_or(false);
+    let authentication = authentication.unwrap_or(false);
+    // By default a signing or authentication subkey is not for encryption
+    let encryption = encryption.unwrap_or_else(|| {
+        if signing || authentication {
+            String::from("none")
+        } else {
+            String::from("storage")
+        }
+    });
+    let mut flags = get_encryption_flags(&encryption)?.unwrap_or_default();
+    let for_encryption = flags.for_storage_encryption() || flags.for_transport_encryption();
+    if signing {
+        flags = flags.set_signing(true);
+    }
+    if authentication {
+        flags = flags.set_authentication(true);
+    }
+    if flags == KeyFlags::default() {
+        return Err(PyValueError::new_err(
+            "The subkey must be for signing, encryption or authentication.",
+        ));
+    }
+    // Only the RSA keys can both sign and encrypt, the ECC keys are either EdDSA/ECDSA or ECDH
+    if for_encryption && (signing || authentication) && !cipher.starts_with("RSA") {
+        return Err(PyValueError::new_err(format!(
+            "A {} subkey can not be used for both signing and encryption.",
+            cipher
+        )));
+    }
+    let mut keypair = get_primary_keypair(&cert, &password)?;
+    let mut subkey = py.allow_threads(|| generate_subkey(&cipher, &flags))?;
+
+    let mut builder = SignatureBuilder::new(SignatureType::SubkeyBinding)
+        .set_key_flags(&flags)
+        .and_then(|builder| builder.set_key_validity_period(expiration.map(Duration::from_secs)))
+        .map_err(to_pyerr)?;
+    if flags.for_signing() || flags.for_certification() {
+        // Signing subkeys need the primary key binding signature (backsig)
+        let mut subkey_signer = subkey
+            .clone()
+            .role_into_unspecified()
+            .into_keypair()
+            .map_err(to_pyerr)?;
+        let backsig = SignatureBuilder::new(SignatureType::PrimaryKeyBinding)
+            .sign_primary_key_binding(&mut subkey_signer, cert.primary_key().key(), &subkey)
+            .map_err(to_pyerr)?;
+        builder = builder.set_embedded_signature(backsig).map_err(to_pyerr)?;
+    }
+    let sig = subkey
+        .bind(&mut keypair, &cert, builder)
+        .map_err(to_pyerr)?;
+
+    let primary_encrypted = cert
+        .primary_key()
+        .key()
+        .optional_secret()
+        .map(|secret| secret.is_encrypted())
+        .unwrap_or(false);
+    if primary_encrypted {
+        subkey
+            .secret_mut()
+            .encrypt_in_place(&openpgp::crypto::Password::from(password))
+            .map_err(to_pyerr)?;
+    }
+    let cert = cert
+        .merge_packets(vec![subkey.into(), sig.into()])
+        .map_err(to_pyerr)?;
+    armored_keys(&cert)
+}
+
+/// This function takes a secret key (Cert object or path), the password and the fingerprint of a
+/// subkey, sets the expiration time of the subkey (in seconds since epoch, None for never) and
+/// returns a tuple of the armored public and secret key.
+#[pyfunction]
+#[text_signature = "(secretkey, password, fingerprint, when)"]
+fn set_subkey_expiration(
+    secretkey: CertOrPath,
+    password: String,
+    fingerprint: String,
+    when: Option<u64>,
+) -> PyResult<(String, String)> {
+    let cert = secretkey.0;
+    let mut keypair = get_primary_keypair(&cert, &password)?;
+    let ka = find_subkey(&cert, &fingerprint)?;
+    // To raise ValueError if the expiration is before the creation time
+    get_validity_period(ka.key(), when)?;
+    let expiration = when.map(|when| UNIX_EPOCH + Duration::from_secs(when));
+    let packets: Vec<openpgp::Packet> = ka
+        .with_policy(&P::new(), None)
+        .map_err(to_pyerr)?
+        .set_expiration_time(&mut keypair, expiration)
+        .map_err(to_pyerr)?
+        .into_iter()
+        .map(|sig| sig.into())
+        .collect();
+    let cert = cert.merge_packets(packets).map_err(to_pyerr)?;
+    armored_keys(&cert)
+}
+
+/// This function takes a secret key (Cert object or path) and the password, sets the expiration
+/// time of the primary key (in seconds since epoch, None for never) and returns a tuple of the
+/// armored public and secret key. If subkeys is True, the same expiration time is set for all
+/// the valid subkeys which are not revoked.
+#[pyfunction]
+#[text_signature = "(secretkey, password, when, subkeys=False)"]
+fn set_expiration(
+    secretkey: CertOrPath,
+    password: String,
+    when: Option<u64>,
+    subkeys: Option<bool>,
+) -> PyResult<(String, String)> {
+    let p = P::new();
+    let cert = secretkey.0;
+    let mut keypair = get_primary_keypair(&cert, &password)?;
+    // To raise ValueError if the expiration is before the creation time
+    get_validity_period(cert.primary_key().key(), when)?;
+    let expiration = when.map(|when| UNIX_EPOCH + Duration::from_secs(when));
+    // This updates the direct key signature and the binding signatures of all the user IDs
+    let mut packets: Vec<openpgp::Packet> = cert
+        .with_policy(&p, None)
+        .map_err(to_pyerr)?
+        .primary_key()
+        .set_expiration_time(&mut keypair, expiration)
+        .map_err(to_pyerr)?
+        .into_iter()
+        .map(|sig| sig.into())
+        .collect();
+    if subkeys.unwrap_or(false) {
+        for ka in cert.keys().subkeys() {
+            match ka.with_policy(&p, None) {
+                Ok(vka) if !matches!(vka.revocation_status(), RevocationStatus::Revoked(_)) => {
+                    get_validity_period(vka.key(), when)?;
+                    packets.extend(
+                        vka.set_expiration_time(&mut keypair, expiration)
+                            .map_err(to_pyerr)?
+                            .into_iter()
+                            .map(|sig| sig.into()),
+                    );
+                }
+                _ => continue,
+            }
+        }
+    }
+    let cert = cert.merge_packets(packets).map_err(to_pyerr)?;
+    armored_keys(&cert)
+}
+
+/// This function takes a secret key (Cert object or path), the password and the fingerprint of a
+/// subkey, returns a tuple of the armored public and secret key with the subkey revoked. The
+/// reason can be "unspecified", "compromised", "superseded" or "retired".
+#[pyfunction]
+#[text_signature = "(secretkey, password, fingerprint, reason=\"unspecified\", message=\"\")"]
+fn revoke_subkey(
+    secretkey: CertOrPath,
+    password: String,
+    fingerprint: String,
+    reason: Option<String>,
+    message: Option<String>,
+) -> PyResult<(String, String)> {
+    let cert = secretkey.0;
+    let code = get_revocation_reason(&reason.unwrap_or_else(|| String::from("unspecified")))?;
+    let mut keypair = get_primary_keypair(&cert, &password)?;
+    let ka = find_subkey(&cert, &fingerprint)?;
+    let sig = SubkeyRevocationBuilder::new()
+        .set_reason_for_revocation(code, message.unwrap_or_default().as_bytes())
+        .map_err(to_pyerr)?
+        .build(&mut keypair, &cert, ka.key(), None)
+        .map_err(to_pyerr)?;
+    let cert = cert.merge_packets(vec![sig.into()]).map_err(to_pyerr)?;
+    armored_keys(&cert)
+}
+
+/// Returns the Certs from the list of Cert objects or paths.
+fn read_certs(publickeys: Vec<CertOrPath>) -> Vec<openpgp::Cert> {
+    publickeys.into_iter().map(|c| c.0).collect()
+}
+
+/// This function takes a list of public keys (Cert objects or paths), the data in bytes and the
+/// detached signature, and verifies the signatures using any of the keys. The VerificationResult
+/// tells which of the keys made the good signatures.
+#[pyfunction]
+#[text_signature = "(publickeys, data, signature)"]
+fn verify_bytes_detached(
+    py: Python,
+    publickeys: Vec<CertOrPath>,
+    data: Vec<u8>,
+    signature: Vec<u8>,
+) -> PyResult<VerificationResult> {
+    let certs = read_certs(publickeys);
+    py.allow_threads(move || -> PyResult<VerificationResult> {
+        let p = &P::new();
+        let mut v = get_detached_verifier(p, certs, &signature[..])?;
+        v.verify_bytes(data).map_err(to_pyerr)?;
+        Ok(v.into_helper().into())
+    })
+}
+
+/// This function takes a list of public keys (Cert objects or paths), the file path and the
+/// detached signature, and verifies the signatures using any of the keys.
+#[pyfunction]
+#[text_signature = "(publickeys, filepath, signature)"]
+fn verify_file_detached(
+    py: Python,
+    publickeys: Vec<CertOrPath>,
+    filepath: Vec<u8>,
+    signature: Vec<u8>,
+) -> PyResult<VerificationResult> {
+    let certs = read_certs(publickeys);
+    py.allow_threads(move || -> PyResult<VerificationResult> {
+        let p = &P::new();
+        let mut v = get_detached_verifier(p, certs, &signature[..])?;
+        v.verify_file(Path::new(str::from_utf8(&filepath[..])?))
+            .map_err(to_pyerr)?;
+        Ok(v.into_helper().into())
+    })
+}
+
+/// This function takes a list of public keys (Cert objects or paths), and encrypts the given data
+/// in bytes to an output file. You can also pass boolen flag armor for armored output.
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+#[text_signature = "(publickeys, data, output, armor=False, wildcard=False, cipher=None, compression=None, aead=None)"]
+fn encrypt_bytes_to_file(
+    py: Python,
+    publickeys: Vec<CertOrPath>,
+    data: Vec<u8>,
+    output: Vec<u8>,
+    armor: Option<bool>,
+    wildcard: Option<bool>,
+    cipher: Option<String>,
+    compression: Option<Compression>,
+    aead: Option<String>,
+) -> PyResult<bool> {
+    let certs = read_certs(publickeys);
+    py.allow_threads(move || -> PyResult<bool> {
+        let p = &P::new();
+        let recipients = get_recipients(p, &certs, wildcard.unwrap_or(false))?;
+
+        let options = EncryptionOptions::new(p, &certs, cipher, compression, aead)?;
+        let mut outfile = OutputFile::create(str::from_utf8(&output[..])?)?;
+        encrypt_internal(
+            recipients,
+            vec![],
+            vec![],
+            &mut &data[..],
+            &mut outfile,
+            armor.unwrap_or(false),
+            &options,
+        )?;
+        outfile.persist()?;
+        Ok(true)
+    })
+}
+
+/// This function takes a list of public keys (Cert objects or paths), and encrypts the given
+/// filepath to an output file. You can also pass boolen flag armor for armored output.
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+#[text_signature = "(publickeys, filepath, output, armor=False, wildcard=False, cipher=None, compression=None, aead=None, progress=None, progress_interval=None)"]
+fn encrypt_file_internal(
+    py: Python,
+    publickeys: Vec<CertOrPath>,
+    filepath: Vec<u8>,
+    output: Vec<u8>,
+    armor: Option<bool>,
+    wildcard: Option<bool>,
+    cipher: Option<String>,
+    compression: Option<Compression>,
+    aead: Option<String>,
+    progress: Option<PyObject>,
+    progress_interval: Option<u64>,
+) -> PyResult<bool> {
+    let certs = read_certs(publickeys);
+    py.allow_threads(move || -> PyResult<bool> {
+        let p = &P::new();
+        let recipients = get_recipients(p, &certs, wildcard.unwrap_or(false))?;
+
+        let options = EncryptionOptions::new(p, &certs, cipher, compression, aead)?;
+        let input = File::open(str::from_utf8(&filepath[..])?)?;
+        let total = input.metadata()?.len();
+        let mut input = ProgressReader::new(input, total, progress, progress_interval);
+        let outputpath = str::from_utf8(&output[..])?;
+        let mut outfile = OutputFile::create(outputpath)?;
+        encrypt_internal(
+            recipients,
+            vec![],
+            vec![],
+            &mut input,
+            &mut outfile,
+            armor.unwrap_or(false),
+            &options,
+        )?;
+        outfile.persist()?;
+        Ok(true)
+    })
+}
+
+/// This function takes a list of public keys (Cert objects or paths), and encrypts the given data
+/// in bytes and returns it. You can also pass boolen flag armor for armored output.
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+#[text_signature = "(publickeys, data, armor=False, wildcard=False, cipher=None, compression=None, aead=None)"]
+fn encrypt_bytes_to_bytes(
+    py: Python,
+    publickeys: Vec<CertOrPath>,
+    data: Vec<u8>,
+    armor: Option<bool>,
+    wildcard: Option<bool>,
+    cipher: Option<String>,
+    compression: Option<Compression>,
+    aead: Option<String>,
+) -> PyResult<PyObject> {
+    let certs = read_certs(publickeys);
+    let result = py.allow_threads(move || -> PyResult<_> {
+        let p = &P::new();
+        let recipients = get_recipients(p, &certs, wildcard.unwrap_or(false))?;
+
+        let options = EncryptionOptions::new(p, &certs, cipher, compression, aead)?;
+        let mut result = Vec::new();
+        encrypt_internal(
+            recipients,
+            vec![],
+            vec![],
+            &mut &data[..],
+            &mut result,
+            armor.unwrap_or(false),
+            &options,
+        )?;
+        Ok(result)
+    })?;
+    let res = PyBytes::new(py, &result);
+    Ok(res.into())
+}
+
+/// Returns the signing keys of the secret key.
+fn get_signing_keys(cert: &openpgp::Cert, password: KeyPassword) -> PyResult<Vec<KeyPair>> {
+    let keys = get_keys(cert, &password)?;
+    if keys.is_empty() {
+        return Err(NoSigningKeyError::new_err("No signing key is present."));
+    }
+    Ok(keys)
+}
+
+/// This function takes a list of public keys, and a secret key with the password, signs and
+/// encrypts the given data in bytes in one pass and returns it. You can also pass boolen flag
+/// armor for armored output.
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+#[text_signature = "(publickeys, secretkey, password, data, armor=False, wildcard=False, cipher=None, compression=None, aead=None)"]
+fn encrypt_and_sign_bytes(
+    py: Python,
+    publickeys: Vec<CertOrPath>,
+    secretkey: CertOrPath,
+    password: KeyPassword,
+    data: Vec<u8>,
+    armor: Option<bool>,
+    wildcard: Option<bool>,
+    cipher: Option<String>,
+    compression: Option<Compression>,
+    aead: Option<String>,
+) -> PyResult<PyObject> {
+    let certs = read_certs(publickeys);
+    let result = py.allow_threads(move || -> PyResult<_> {
+        let p = &P::new();
+        let recipients = get_recipients(p, &certs, wildcard.unwrap_or(false))?;
+        let signers = get_signing_keys(&secretkey.0, password)?;
+
+        let options = EncryptionOptions::new(p, &certs, cipher, compression, aead)?;
+        let mut result = Vec::new();
+        encrypt_internal(
+            recipients,
+            vec![],
+            signers,
+            &mut &data[..],
+            &mut result,
+            armor.unwrap_or(false),
+            &options,
+        )?;
+        Ok(result)
+    })?;
+    let res = PyBytes::new(py, &result);
+    Ok(res.into())
+}
+
+/// This function takes a list of public keys, and a secret key with the password, signs and
+/// encrypts the given filepath in one pass to an output file. You can also pass boolen flag
+/// armor for armored output.
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+#[text_signature = "(publickeys, secretkey, password, filepath, output, armor=False, wildcard=False, cipher=None, compression=None, aead=None)"]
+fn encrypt_and_sign_file(
+    py: Python,
+    publickeys: Vec<CertOrPath>,
+    secretkey: CertOrPath,
+    password: KeyPassword,
     filepath: Vec<u8>,
     output: Vec<u8>,
     armor: Option<bool>,
+    wildcard: Option<bool>,
+    cipher: Option<String>,
+    compression: Option<Compression>,
+    aead: Option<String>,
 ) -> PyResult<bool> {
-    let mut certs = Vec::new();
-    for fpath in publickeys {
-        if !std::fs::metadata(fpath.clone()).is_ok() {
-            return Err(PyFileNotFoundError::new_err(format!(
-                "{} is not found.",
-                fpath
-            )));
+    let certs = read_certs(publickeys);
+    py.allow_threads(move || -> PyResult<bool> {
+        let p = &P::new();
+        let recipients = get_recipients(p, &certs, wildcard.unwrap_or(false))?;
+        let signers = get_signing_keys(&secretkey.0, password)?;
+
+        let options = EncryptionOptions::new(p, &certs, cipher, compression, aead)?;
+        let mut input = File::open(str::from_utf8(&filepath[..])?)?;
+        let mut outfile = OutputFile::create(str::from_utf8(&output[..])?)?;
+        encrypt_internal(
+            recipients,
+            vec![],
+            signers,
+            &mut input,
+            &mut outfile,
+            armor.unwrap_or(false),
+            &options,
+        )?;
+        outfile.persist()?;
+        Ok(true)
+    })
+}
+
+/// This function encrypts the given data in bytes with the password (no public keys) and returns it.
+/// You can also pass boolen flag armor for armored output.
+#[pyfunction]
+#[text_signature = "(data, password, armor=False, cipher=None, compression=None, aead=None)"]
+fn encrypt_bytes_with_password(
+    py: Python,
+    data: Vec<u8>,
+    password: String,
+    armor: Option<bool>,
+    cipher: Option<String>,
+    compression: Option<Compression>,
+    aead: Option<String>,
+) -> PyResult<PyObject> {
+    let result = py.allow_threads(move || -> PyResult<_> {
+        let p = &P::new();
+        let options = EncryptionOptions::new(p, &[], cipher, compression, aead)?;
+        let mut result = Vec::new();
+        encrypt_internal(
+            vec![],
+            vec![password.into()],
+            vec![],
+            &mut &data[..],
+            &mut result,
+            armor.unwrap_or(false),
+            &options,
+        )?;
+        Ok(result)
+    })?;
+    let res = PyBytes::new(py, &result);
+    Ok(res.into())
+}
+
+/// This function encrypts the given filepath with the password (no public keys) to an output
+/// file. You can also pass boolen flag armor for armored output.
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+#[text_signature = "(filepath, output, password, armor=False, cipher=None, compression=None, aead=None)"]
+fn encrypt_file_with_password(
+    py: Python,
+    filepath: Vec<u8>,
+    output: Vec<u8>,
+    password: String,
+    armor: Option<bool>,
+    cipher: Option<String>,
+    compression: Option<Compression>,
+    aead: Option<String>,
+) -> PyResult<bool> {
+    py.allow_threads(move || -> PyResult<bool> {
+        let p = &P::new();
+        let options = EncryptionOptions::new(p, &[], cipher, compression, aead)?;
+        let mut input = File::open(str::from_utf8(&filepath[..])?)?;
+        let mut outfile = OutputFile::create(str::from_utf8(&output[..])?)?;
+        encrypt_internal(
+            vec![],
+            vec![password.into()],
+            vec![],
+            &mut input,
+            &mut outfile,
+            armor.unwrap_or(false),
+            &options,
+        )?;
+        outfile.persist()?;
+        Ok(true)
+    })
+}
+
+/// This function decrypts the given data in bytes which was encrypted with a password.
+#[pyfunction]
+#[text_signature = "(data, password)"]
+fn decrypt_bytes_with_password(
+    py: Python,
+    data: Vec<u8>,
+    password: String,
+) -> PyResult<DecryptionResult> {
+    let (helper, result) = py.allow_threads(move || -> PyResult<_> {
+        let p = &P::new();
+
+        let mut result = Vec::new();
+        let mut decryptor = get_decryptor(p, &data[..], Helper::from_password(&password))?;
+        std::io::copy(&mut decryptor, &mut result).map_err(|e| to_pyerr(e.into()))?;
+        Ok((decryptor.into_helper(), result))
+    })?;
+    let res = PyBytes::new(py, &result);
+    get_decryption_result(helper, res.into(), None)
+}
+
+/// This function decrypts the given filepath which was encrypted with a password to the output
+/// file.
+#[pyfunction]
+#[text_signature = "(filepath, output, password)"]
+fn decrypt_file_with_password(
+    py: Python,
+    filepath: Vec<u8>,
+    output: Vec<u8>,
+    password: String,
+) -> PyResult<DecryptionResult> {
+    let (helper, outputpath) = py.allow_threads(move || -> PyResult<_> {
+        let p = &P::new();
+
+        let inputpath = str::from_utf8(&filepath[..])?;
+        let outputpath = str::from_utf8(&output[..])?;
+        let input = File::open(inputpath)?;
+        let mut decryptor = get_decryptor(p, input, Helper::from_password(&password))?;
+        let mut outfile = OutputFile::create(outputpath)?;
+        std::io::copy(&mut decryptor, &mut outfile).map_err(|e| to_pyerr(e.into()))?;
+        outfile.persist()?;
+        Ok((decryptor.into_helper(), outputpath.to_string()))
+    })?;
+    get_decryption_result(helper, py.None(), Some(outputpath))
+}
+
+/// This function decrypts the given data using the secret keys (Cert objects or paths), the key
+/// is selected based on the recipients of the message. The password can be a str for all the
+/// keys, or a dict of the fingerprints to the passwords, only the password of the key which can
+/// decrypt the message is used.
+#[pyfunction]
+#[text_signature = "(secretkeys, data, password)"]
+fn decrypt_bytes_with_keys(
+    py: Python,
+    secretkeys: Vec<CertOrPath>,
+    data: Vec<u8>,
+    password: KeyPassword,
+) -> PyResult<DecryptionResult> {
+    let (helper, result) = py.allow_threads(move || -> PyResult<_> {
+        let p = &P::new();
+
+        let mut result = Vec::new();
+        let helper = Helper::from_certs(p, &read_certs(secretkeys), password)?;
+        let mut decryptor = get_decryptor(p, &data[..], helper)?;
+        std::io::copy(&mut decryptor, &mut result).map_err(|e| to_pyerr(e.into()))?;
+        Ok((decryptor.into_helper(), result))
+    })?;
+    let res = PyBytes::new(py, &result);
+    get_decryption_result(helper, res.into(), None)
+}
+
+/// This function decrypts the given filepath to the output file using the secret keys (Cert
+/// objects or paths), the key is selected based on the recipients of the message.
+#[pyfunction]
+#[text_signature = "(secretkeys, filepath, output, password, progress=None, progress_interval=None)"]
+fn decrypt_file_with_keys(
+    py: Python,
+    secretkeys: Vec<CertOrPath>,
+    filepath: Vec<u8>,
+    output: Vec<u8>,
+    password: KeyPassword,
+    progress: Option<PyObject>,
+    progress_interval: Option<u64>,
+) -> PyResult<DecryptionResult> {
+    let (helper, outputpath) = py.allow_threads(move || -> PyResult<_> {
+        let p = &P::new();
+
+        let inputpath = str::from_utf8(&filepath[..])?;
+        let outputpath = str::from_utf8(&output[..])?;
+        let input = File::open(inputpath)?;
+        let total = input.metadata()?.len();
+        let input = ProgressReader::new(input, total, progress, progress_interval);
+        let helper = Helper::from_certs(p, &read_certs(secretkeys), password)?;
+        let mut decryptor = get_decryptor(p, input, helper)?;
+        let mut outfile = OutputFile::create(outputpath)?;
+        std::io::copy(&mut decryptor, &mut outfile).map_err(|e| to_pyerr(e.into()))?;
+        outfile.persist()?;
+        Ok((decryptor.into_helper(), outputpath.to_string()))
+    })?;
+    get_decryption_result(helper, py.None(), Some(outputpath))
+}
+
+/// This function takes a list of public keys (Cert objects or paths), and encrypts the data read
+/// from the input file object (with a read() method) to the output file object (with a write()
+/// method), so that the data is never fully in memory.
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+#[text_signature = "(publickeys, input, output, armor=False, wildcard=False, cipher=None, compression=None, aead=None)"]
+fn encrypt_stream(
+    py: Python,
+    publickeys: Vec<CertOrPath>,
+    input: PyObject,
+    output: PyObject,
+    armor: Option<bool>,
+    wildcard: Option<bool>,
+    cipher: Option<String>,
+    compression: Option<Compression>,
+    aead: Option<String>,
+) -> PyResult<bool> {
+    let certs = read_certs(publickeys);
+    py.allow_threads(move || -> PyResult<bool> {
+        let p = &P::new();
+        let recipients = get_recipients(p, &certs, wildcard.unwrap_or(false))?;
+        let options = EncryptionOptions::new(p, &certs, cipher, compression, aead)?;
+        let mut writer = PyWriter(output);
+        encrypt_internal(
+            recipients,
+            vec![],
+            vec![],
+            &mut PyReader(input),
+            &mut writer,
+            armor.unwrap_or(false),
+            &options,
+        )?;
+        writer.flush().map_err(|e| to_pyerr(e.into()))?;
+        Ok(true)
+    })
+}
+
+/// This function decrypts the data read from the input file object to the output file object
+/// using the secret keys (Cert objects or paths), the key is selected based on the recipients of
+/// the message.
+#[pyfunction]
+#[text_signature = "(secretkeys, input, output, password)"]
+fn decrypt_stream(
+    py: Python,
+    secretkeys: Vec<CertOrPath>,
+    input: PyObject,
+    output: PyObject,
+    password: KeyPassword,
+) -> PyResult<DecryptionResult> {
+    let helper = py.allow_threads(move || -> PyResult<_> {
+        let p = &P::new();
+
+        let helper = Helper::from_certs(p, &read_certs(secretkeys), password)?;
+        let mut decryptor = get_decryptor(p, PyReader(input), helper)?;
+        let mut writer = PyWriter(output);
+        std::io::copy(&mut decryptor, &mut writer).map_err(|e| to_pyerr(e.into()))?;
+        writer.flush().map_err(|e| to_pyerr(e.into()))?;
+        Ok(decryptor.into_helper())
+    })?;
+    get_decryption_result(helper, py.None(), None)
+}
+
+/// How many chunks can wait for the worker thread of a stream, before write() blocks.
+const STREAM_QUEUE_SIZE: usize = 16;
+
+/// Reads the chunks written to a stream, in the worker thread. The end of the input is reached
+/// when the stream is finished.
+struct ChannelReader {
+    receiver: Receiver<Vec<u8>>,
+    chunk: Vec<u8>,
+    pos: usize,
+}
+
+impl io::Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.chunk.len() {
+            self.chunk = match self.receiver.recv() {
+                Ok(chunk) => chunk,
+                // The sender is dropped when the stream is finished
+                Err(_) => return Ok(0),
+            };
+            self.pos = 0;
         }
-        certs.push(openpgp::Cert::from_file(&fpath).unwrap());
+        let size = buf.len().min(self.chunk.len() - self.pos);
+        buf[..size].copy_from_slice(&self.chunk[self.pos..self.pos + size]);
+        self.pos += size;
+        Ok(size)
     }
-    let mode = KeyFlags::default().set_storage_encryption(true);
+}
 
-    let p = &P::new();
-    let recipients = certs.iter().flat_map(|cert| {
-        cert.keys()
-            .with_policy(p, None)
-            .alive()
-            .revoked(false)
-            .key_flags(&mode)
-    });
+/// Collects the output of the worker thread, which is returned from write() and finish().
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
 
-    let mut input = File::open(str::from_utf8(&filepath[..]).unwrap()).unwrap();
-    let mut outfile = File::create(str::from_utf8(&output[..]).unwrap()).unwrap();
-    // TODO: Find better ways to write this code
-    match armor {
-        // For armored output file.
-        Some(true) => {
-            let mut sink = armor::Writer::new(&mut outfile, armor::Kind::Message).unwrap();
-            // Stream an OpenPGP message.
-            let message = Message::new(&mut sink);
+impl SharedBuffer {
+    fn take(&self) -> Vec<u8> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
 
-            // We want to encrypt a literal data packet.
-            let encryptor = Encryptor::for_recipients(message, recipients)
-                .build()
-                .expect("Failed to create encryptor");
+impl io::Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
 
-            let mut literal_writer = LiteralWriter::new(encryptor)
-                .build()
-                .expect("Failed to create literal writer");
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
 
-            // Copy stdin to our writer stack to encrypt the data.
-            io::copy(&mut input, &mut literal_writer).expect("Failed to encrypt");
-            //literal_writer.write_all(&data).unwrap();
+/// Runs the sequoia reader/writer stack of a stream in a thread, as the stack borrows the keys
+/// and the policy, and can not be kept inside of a Python object.
+struct StreamWorker<T> {
+    sender: Option<SyncSender<Vec<u8>>>,
+    output: SharedBuffer,
+    worker: Option<thread::JoinHandle<PyResult<T>>>,
+}
 
-            // Finally, finalize the OpenPGP message by tearing down the
-            // writer stack.
-            literal_writer.finalize().unwrap();
+impl<T: Send + 'static> StreamWorker<T> {
+    fn spawn<F>(f: F) -> Self
+    where
+        F: FnOnce(ChannelReader, SharedBuffer) -> PyResult<T> + Send + 'static,
+    {
+        let (sender, receiver) = sync_channel(STREAM_QUEUE_SIZE);
+        let output = SharedBuffer::default();
+        let reader = ChannelReader {
+            receiver,
+            chunk: Vec::new(),
+            pos: 0,
+        };
+        let writer = output.clone();
+        StreamWorker {
+            sender: Some(sender),
+            output,
+            worker: Some(thread::spawn(move || f(reader, writer))),
+        }
+    }
 
-            // Finalize the armor writer.
-            sink.finalize().expect("Failed to write data");
+    /// Passes the chunk to the worker, and returns the output available till now.
+    fn write(&mut self, py: Python, chunk: Vec<u8>) -> PyResult<Vec<u8>> {
+        let sender = match self.sender {
+            Some(ref sender) => sender.clone(),
+            None => return Err(PyValueError::new_err("The stream is already finished.")),
+        };
+        // The GIL is released, the worker may need it for the password callback
+        if py.allow_threads(move || sender.send(chunk)).is_err() {
+            // The worker stopped early, most probably because of an error
+            self.finish(py)?;
+            return Err(CryptoError::new_err(
+                "The stream ended before all the data was written.",
+            ));
         }
-        _ => {
-            let message = Message::new(&mut outfile);
+        Ok(self.output.take())
+    }
 
-            // We want to encrypt a literal data packet.
-            let encryptor = Encryptor::for_recipients(message, recipients)
-                .build()
-                .expect("Failed to create encryptor");
+    /// Ends the input, waits for the worker and returns the rest of the output.
+    fn finish(&mut self, py: Python) -> PyResult<(Vec<u8>, T)> {
+        self.sender = None;
+        let worker = self
+            .worker
+            .take()
+            .ok_or_else(|| PyValueError::new_err("The stream is already finished."))?;
+        let result = py
+            .allow_threads(move || worker.join())
+            .map_err(|_| CryptoError::new_err("The worker of the stream panicked."))??;
+        Ok((self.output.take(), result))
+    }
+}
 
-            let mut literal_writer = LiteralWriter::new(encryptor)
-                .build()
-                .expect("Failed to create literal writer");
+/// Encrypts the data written in chunks, for the data which is not available all at once.
+#[pyclass]
+#[text_signature = "(publickeys, armor=False, wildcard=False, cipher=None, compression=None, aead=None)"]
+struct EncryptionStream {
+    worker: StreamWorker<()>,
+}
 
-            // Copy stdin to our writer stack to encrypt the data.
-            io::copy(&mut input, &mut literal_writer).expect("Failed to encrypt");
-            //literal_writer.write_all(&data).unwrap();
+#[pymethods]
+impl EncryptionStream {
+    #[new]
+    fn new(
+        publickeys: Vec<CertOrPath>,
+        armor: Option<bool>,
+        wildcard: Option<bool>,
+        cipher: Option<String>,
+        compression: Option<Compression>,
+        aead: Option<String>,
+    ) -> PyResult<Self> {
+        let certs = read_certs(publickeys);
+        let armor = armor.unwrap_or(false);
+        let wildcard = wildcard.unwrap_or(false);
+        // Any error in the keys or the options is raised here, and not from the worker
+        let p = &P::new();
+        get_recipients(p, &certs, wildcard)?;
+        let options = EncryptionOptions::new(p, &certs, cipher, compression, aead)?;
+
+        let worker = StreamWorker::spawn(move |mut reader, mut writer| {
+            let p = &P::new();
+            let recipients = get_recipients(p, &certs, wildcard)?;
+            encrypt_internal(
+                recipients,
+                vec![],
+                vec![],
+                &mut reader,
+                &mut writer,
+                armor,
+                &options,
+            )
+        });
+        Ok(EncryptionStream { worker })
+    }
 
-            // Finally, finalize the OpenPGP message by tearing down the
-            // writer stack.
-            literal_writer.finalize().unwrap();
-        }
+    /// Encrypts the chunk of data, and returns the encrypted bytes available till now.
+    #[text_signature = "($self, chunk)"]
+    fn write(&mut self, py: Python, chunk: Vec<u8>) -> PyResult<PyObject> {
+        let output = self.worker.write(py, chunk)?;
+        Ok(PyBytes::new(py, &output).into())
     }
 
-    Ok(true)
+    /// Finishes the encryption, and returns the rest of the encrypted bytes.
+    #[text_signature = "($self)"]
+    fn finish(&mut self, py: Python) -> PyResult<PyObject> {
+        let (output, _) = self.worker.finish(py)?;
+        Ok(PyBytes::new(py, &output).into())
+    }
 }
 
-/// This function takes a list of public key paths, and encrypts the given data in bytes and returns it.
-/// You can also pass boolen flag armor for armored output.
-#[pyfunction]
-#[text_signature = "(publickeys, data, armor=False)"]
-fn encrypt_bytes_to_bytes(
-    py: Python,
-    publickeys: Vec<String>,
-    data: Vec<u8>,
-    armor: Option<bool>,
-) -> PyResult<PyObject> {
-    let mut certs = Vec::new();
-    for fpath in publickeys {
-        if !std::fs::metadata(fpath.clone()).is_ok() {
-            return Err(PyFileNotFoundError::new_err(format!(
-                "{} is not found.",
-                fpath
-            )));
-        }
-        certs.push(openpgp::Cert::from_file(&fpath).unwrap());
+/// Decrypts the data written in chunks using the secret keys, the key is selected based on the
+/// recipients of the message.
+#[pyclass]
+#[text_signature = "(secretkeys, password)"]
+struct DecryptionStream {
+    worker: StreamWorker<Helper>,
+}
+
+#[pymethods]
+impl DecryptionStream {
+    #[new]
+    fn new(secretkeys: Vec<CertOrPath>, password: KeyPassword) -> PyResult<Self> {
+        let helper = Helper::from_certs(&P::new(), &read_certs(secretkeys), password)?;
+        let worker = StreamWorker::spawn(move |reader, mut writer| {
+            let p = &P::new();
+            let mut decryptor = get_decryptor(p, reader, helper)?;
+            io::copy(&mut decryptor, &mut writer).map_err(|e| to_pyerr(e.into()))?;
+            Ok(decryptor.into_helper())
+        });
+        Ok(DecryptionStream { worker })
     }
-    let mode = KeyFlags::default().set_storage_encryption(true);
 
-    let p = &P::new();
-    let recipients = certs.iter().flat_map(|cert| {
-        cert.keys()
-            .with_policy(p, None)
-            .alive()
-            .revoked(false)
-            .key_flags(&mode)
-    });
-    // TODO: Find better way to do this in rust
-    let mut result = Vec::new();
-    let mut result2 = Vec::new();
-    let mut sink = armor::Writer::new(&mut result2, armor::Kind::Message)?;
-    // Stream an OpenPGP message.
-    let message = match armor {
-        Some(true) => Message::new(&mut sink),
-        _ => Message::new(&mut result),
-    };
-    // We want to encrypt a literal data packet.
-    let encryptor = Encryptor::for_recipients(message, recipients)
-        .build()
-        .expect("Failed to create encryptor");
+    /// Decrypts the chunk of the encrypted message, and returns the plain text available till
+    /// now. The plain text is only authenticated when finish() succeeds.
+    #[text_signature = "($self, chunk)"]
+    fn write(&mut self, py: Python, chunk: Vec<u8>) -> PyResult<PyObject> {
+        let output = self.worker.write(py, chunk)?;
+        Ok(PyBytes::new(py, &output).into())
+    }
+
+    /// Finishes the decryption, and returns the DecryptionResult, its plaintext is the rest of
+    /// the plain text which was not returned from write().
+    #[text_signature = "($self)"]
+    fn finish(&mut self, py: Python) -> PyResult<DecryptionResult> {
+        let (output, helper) = self.worker.finish(py)?;
+        get_decryption_result(helper, PyBytes::new(py, &output).into(), None)
+    }
+}
+
+/// The result of a decryption, along with the details of the message.
+#[pyclass]
+struct DecryptionResult {
+    // The decrypted bytes, None when decrypted to a file
+    #[pyo3(get)]
+    plaintext: PyObject,
+    // The path of the decrypted file
+    #[pyo3(get)]
+    output: Option<String>,
+    // The fingerprint of the (sub)key which decrypted the message, None for password
+    #[pyo3(get)]
+    fingerprint: Option<String>,
+    // Key IDs of all the recipients of the message
+    #[pyo3(get)]
+    recipients: Vec<String>,
+    #[pyo3(get)]
+    sym_algo: Option<String>,
+    // From the literal data packet
+    #[pyo3(get)]
+    filename: Option<String>,
+    #[pyo3(get)]
+    date: Option<u64>,
+    #[pyo3(get)]
+    format: Option<String>,
+    // The signatures inside of the encrypted message
+    verification: VerificationResult,
+}
 
-    let mut literal_writer = LiteralWriter::new(encryptor)
-        .build()
-        .expect("Failed to create literal writer");
+#[pymethods]
+impl DecryptionResult {
+    /// The VerificationResult of the signatures inside of the encrypted message.
+    #[getter]
+    fn verification(&self) -> VerificationResult {
+        self.verification.clone()
+    }
 
-    // Copy stdin to our writer stack to encrypt the data.
-    // io::copy(&mut data, &mut literal_writer).expect("Failed to encrypt");
-    literal_writer.write_all(&data).unwrap();
+    /// Fingerprints of the signers with good signatures.
+    #[getter]
+    fn verified(&self) -> Vec<String> {
+        self.verification.verified()
+    }
 
-    // Finally, finalize the OpenPGP message by tearing down the
-    // writer stack.
-    literal_writer.finalize().unwrap();
+    /// Number of the signatures which are not good, the ones made by unknown keys (not in the
+    /// signers) are not counted.
+    #[getter]
+    fn bad_signatures(&self) -> usize {
+        self.verification
+            .signatures
+            .iter()
+            .filter(|info| info.status != "good" && info.status != "unknown_key")
+            .count()
+    }
+}
+
+/// The result of a signature verification, with the details of each signature. It is true if
+/// there is at least one good signature.
+#[pyclass]
+#[derive(Debug, Clone)]
+struct VerificationResult {
+    signatures: Vec<SignatureInfo>,
+}
 
-    match armor {
-        Some(true) => {
-            // Finalize the armor writer.
-            sink.finalize().expect("Failed to write data");
-            let res = PyBytes::new(py, &result2);
-            return Ok(res.into());
+impl From<VHelper> for VerificationResult {
+    fn from(helper: VHelper) -> Self {
+        VerificationResult {
+            signatures: helper.signatures,
         }
-        _ => {
-            let res = PyBytes::new(py, &result);
-            return Ok(res.into());
+    }
+}
+
+#[pymethods]
+impl VerificationResult {
+    /// True if there is at least one good signature.
+    #[getter]
+    fn valid(&self) -> bool {
+        self.signatures.iter().any(|info| info.status == "good")
+    }
+
+    /// List of dictionaries with the details of each signature.
+    #[getter]
+    fn signatures(&self, py: Python) -> PyResult<PyObject> {
+        let signatures = PyList::empty(py);
+        for info in &self.signatures {
+            signatures.append(info.to_dict(py)?)?;
+        }
+        Ok(signatures.into())
+    }
+
+    /// Returns true if at least n different certs made good signatures, for "n of m signers".
+    #[text_signature = "($self, n)"]
+    fn has_good_signers(&self, n: usize) -> bool {
+        self.verified().len() >= n
+    }
+
+    /// Fingerprints of the certs which made the good signatures.
+    #[getter]
+    fn verified(&self) -> Vec<String> {
+        let mut verified: Vec<String> = Vec::new();
+        for info in self.signatures.iter().filter(|info| info.status == "good") {
+            if let Some(fingerprint) = &info.fingerprint {
+                if !verified.contains(fingerprint) {
+                    verified.push(fingerprint.clone());
+                }
+            }
         }
+        verified
+    }
+}
+
+#[pyproto]
+impl PyObjectProtocol for VerificationResult {
+    fn __bool__(&self) -> bool {
+        self.valid()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "<VerificationResult valid={} signatures={}>",
+            self.valid(),
+            self.signatures.len()
+        )
     }
 }
 
 #[pyclass]
 #[derive(Debug)]
 struct Johnny {
+    // None when created from a Cert object
     #[pyo3(get, set)]
-    filepath: String,
+    filepath: Option<String>,
     cert: openpgp::cert::Cert,
 }
 
 #[pymethods]
 impl Johnny {
     #[new]
-    fn new(filepath: String) -> PyResult<Self> {
-        if !std::fs::metadata(filepath.clone()).is_ok() {
-            return Err(PyFileNotFoundError::new_err(format!(
-                "{} is not found.",
-                filepath
-            )));
-        }
-        let cert = openpgp::Cert::from_file(&filepath).unwrap();
+    fn new(key: &PyAny) -> PyResult<Self> {
+        let filepath = key.extract::<String>().ok();
+        let cert = key.extract::<CertOrPath>()?.0;
         Ok(Johnny { filepath, cert })
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn encrypt_bytes(
         &self,
         py: Python,
         data: Vec<u8>,
         armor: Option<bool>,
+        wildcard: Option<bool>,
+        cipher: Option<String>,
+        compression: Option<Compression>,
+        aead: Option<String>,
     ) -> PyResult<PyObject> {
-        let mode = KeyFlags::default().set_storage_encryption(true);
-        let p = &P::new();
-        let recipients = self
-            .cert
-            .keys()
-            .with_policy(p, None)
-            .alive()
-            .revoked(false)
-            .key_flags(&mode);
-        // TODO: Find better way to do this in rust
-        let mut result = Vec::new();
-        let mut result2 = Vec::new();
-        let mut sink = armor::Writer::new(&mut result2, armor::Kind::Message)?;
-        // Stream an OpenPGP message.
-        let message = match armor {
-            Some(true) => Message::new(&mut sink),
-            _ => Message::new(&mut result),
-        };
-        // We want to encrypt a literal data packet.
-        let encryptor = Encryptor::for_recipients(message, recipients)
-            .build()
-            .expect("Failed to create encryptor");
-
-        let mut literal_writer = LiteralWriter::new(encryptor)
-            .build()
-            .expect("Failed to create literal writer");
-
-        // Copy stdin to our writer stack to encrypt the data.
-        // io::copy(&mut data, &mut literal_writer).expect("Failed to encrypt");
-        literal_writer.write_all(&data).unwrap();
-
-        // Finally, finalize the OpenPGP message by tearing down the
-        // writer stack.
-        literal_writer.finalize().unwrap();
-
-        match armor {
-            Some(true) => {
-                // Finalize the armor writer.
-                sink.finalize().expect("Failed to write data");
-                let res = PyBytes::new(py, &result2);
-                return Ok(res.into());
-            }
-            _ => {
-                let res = PyBytes::new(py, &result);
-                return Ok(res.into());
-            }
-        }
+        let cert = self.cert.clone();
+        let result = py.allow_threads(move || -> PyResult<_> {
+            let p = &P::new();
+            let recipients =
+                get_recipients(p, std::slice::from_ref(&cert), wildcard.unwrap_or(false))?;
+
+            let options =
+                EncryptionOptions::new(p, std::slice::from_ref(&cert), cipher, compression, aead)?;
+            let mut result = Vec::new();
+            encrypt_internal(
+                recipients,
+                vec![],
+                vec![],
+                &mut &data[..],
+                &mut result,
+                armor.unwrap_or(false),
+                &options,
+            )?;
+            Ok(result)
+        })?;
+        let res = PyBytes::new(py, &result);
+        Ok(res.into())
     }
 
-    pub fn decrypt_bytes(&self, py: Python, data: Vec<u8>, password: String) -> PyResult<PyObject> {
-        let p = &NP::new();
-
-        let mut result = Vec::new();
-        let reader = std::io::BufReader::new(&data[..]);
-
-        let dec = DecryptorBuilder::from_reader(reader);
-        let dec2 = match dec {
-            Ok(dec) => dec,
-            Err(msg) => panic!(msg),
-        };
-        let mut decryptor = match dec2.with_policy(p, None, Helper::new(p, &self.cert, &password)) {
-            Ok(decr) => decr,
-            Err(msg) => panic!(msg),
-        };
-        std::io::copy(&mut decryptor, &mut result).unwrap();
+    pub fn decrypt_bytes(
+        &self,
+        py: Python,
+        data: Vec<u8>,
+        password: KeyPassword,
+        passphrase: Option<String>,
+        signers: Option<Vec<CertOrPath>>,
+    ) -> PyResult<DecryptionResult> {
+        let cert = self.cert.clone();
+        let (helper, result) = py.allow_threads(move || -> PyResult<_> {
+            let p = &P::new();
+
+            let mut result = Vec::new();
+            let reader = std::io::BufReader::new(&data[..]);
+
+            let certs = read_certs(signers.unwrap_or_default());
+            let helper = Helper::new(p, &cert, password)?
+                .set_password(passphrase)
+                .set_signers(certs);
+            let mut decryptor = get_decryptor(p, reader, helper)?;
+            std::io::copy(&mut decryptor, &mut result).map_err(|e| to_pyerr(e.into()))?;
+            Ok((decryptor.into_helper(), result))
+        })?;
         let res = PyBytes::new(py, &result);
-        Ok(res.into())
+        get_decryption_result(helper, res.into(), None)
     }
+    #[allow(clippy::too_many_arguments)]
     pub fn encrypt_file(
         &self,
+        py: Python,
         filepath: Vec<u8>,
         output: Vec<u8>,
         armor: Option<bool>,
+        wildcard: Option<bool>,
+        cipher: Option<String>,
+        compression: Option<Compression>,
+        aead: Option<String>,
     ) -> PyResult<bool> {
-        let mode = KeyFlags::default().set_storage_encryption(true);
-        let p = &P::new();
-        let recipients = self
-            .cert
-            .keys()
-            .with_policy(p, None)
-            .alive()
-            .revoked(false)
-            .key_flags(&mode);
-        let mut input = File::open(str::from_utf8(&filepath[..]).unwrap()).unwrap();
-        let mut outfile = File::create(str::from_utf8(&output[..]).unwrap()).unwrap();
-        // TODO: Find better ways to write this code
-        match armor {
-            // For armored output file.
-            Some(true) => {
-                let mut sink = armor::Writer::new(&mut outfile, armor::Kind::Message).unwrap();
-                // Stream an OpenPGP message.
-                let message = Message::new(&mut sink);
-
-                // We want to encrypt a literal data packet.
-                let encryptor = Encryptor::for_recipients(message, recipients)
-                    .build()
-                    .expect("Failed to create encryptor");
-
-                let mut literal_writer = LiteralWriter::new(encryptor)
-                    .build()
-                    .expect("Failed to create literal writer");
-
-                // Copy stdin to our writer stack to encrypt the data.
-                io::copy(&mut input, &mut literal_writer).expect("Failed to encrypt");
-                //literal_writer.write_all(&data).unwrap();
-
-                // Finally, finalize the OpenPGP message by tearing down the
-                // writer stack.
-                literal_writer.finalize().unwrap();
-
-                // Finalize the armor writer.
-                sink.finalize().expect("Failed to write data");
-            }
-            _ => {
-                let message = Message::new(&mut outfile);
-
-                // We want to encrypt a literal data packet.
-                let encryptor = Encryptor::for_recipients(message, recipients)
-                    .build()
-                    .expect("Failed to create encryptor");
+        let cert = self.cert.clone();
+        py.allow_threads(move || -> PyResult<bool> {
+            let p = &P::new();
+            let recipients =
+                get_recipients(p, std::slice::from_ref(&cert), wildcard.unwrap_or(false))?;
+
+            let options =
+                EncryptionOptions::new(p, std::slice::from_ref(&cert), cipher, compression, aead)?;
+            let mut input = File::open(str::from_utf8(&filepath[..])?)?;
+            let mut outfile = OutputFile::create(str::from_utf8(&output[..])?)?;
+            encrypt_internal(
+                recipients,
+                vec![],
+                vec![],
+                &mut input,
+                &mut outfile,
+                armor.unwrap_or(false),
+                &options,
+            )?;
+            outfile.persist()?;
+            Ok(true)
+        })
+    }
 
-                let mut literal_writer = LiteralWriter::new(encryptor)
-                    .build()
-                    .expect("Failed to create literal writer");
+    #[allow(clippy::too_many_arguments)]
+    pub fn decrypt_file(
+        &self,
+        py: Python,
+        filepath: Vec<u8>,
+        output: Vec<u8>,
+        password: KeyPassword,
+        passphrase: Option<String>,
+        signers: Option<Vec<CertOrPath>>,
+        progress: Option<PyObject>,
+        progress_interval: Option<u64>,
+    ) -> PyResult<DecryptionResult> {
+        let cert = self.cert.clone();
+        let (helper, outputpath) = py.allow_threads(move || -> PyResult<_> {
+            let p = &P::new();
+
+            let inputpath = str::from_utf8(&filepath[..])?;
+            let outputpath = str::from_utf8(&output[..])?;
+            let input = File::open(inputpath)?;
+            let total = input.metadata()?.len();
+            let input = ProgressReader::new(input, total, progress, progress_interval);
+            let certs = read_certs(signers.unwrap_or_default());
+            let helper = Helper::new(p, &cert, password)?
+                .set_password(passphrase)
+                .set_signers(certs);
+            let mut decryptor = get_decryptor(p, input, helper)?;
+            let mut outfile = OutputFile::create(outputpath)?;
+            std::io::copy(&mut decryptor, &mut outfile).map_err(|e| to_pyerr(e.into()))?;
+            outfile.persist()?;
+            Ok((decryptor.into_helper(), outputpath.to_string()))
+        })?;
+        get_decryption_result(helper, py.None(), Some(outputpath))
+    }
 
-                // Copy stdin to our writer stack to encrypt the data.
-                io::copy(&mut input, &mut literal_writer).expect("Failed to encrypt");
-                //literal_writer.write_all(&data).unwrap();
+    pub fn sign_bytes_detached(
+        &self,
+        py: Python,
+        data: Vec<u8>,
+        password: KeyPassword,
+    ) -> PyResult<String> {
+        let cert = self.cert.clone();
+        py.allow_threads(move || -> PyResult<String> {
+            let mut localdata = io::Cursor::new(data);
+            sign_bytes_detached_internal(&cert, &mut localdata, password)
+        })
+    }
 
-                // Finally, finalize the OpenPGP message by tearing down the
-                // writer stack.
-                literal_writer.finalize().unwrap();
-            }
-        }
+    pub fn sign_file_detached(
+        &self,
+        py: Python,
+        filepath: Vec<u8>,
+        password: KeyPassword,
+        progress: Option<PyObject>,
+        progress_interval: Option<u64>,
+    ) -> PyResult<String> {
+        let cert = self.cert.clone();
+        py.allow_threads(move || -> PyResult<String> {
+            let file = Path::new(str::from_utf8(&filepath[..])?);
+            let file = File::open(file)?;
+            let total = file.metadata()?.len();
+            let mut localdata = ProgressReader::new(file, total, progress, progress_interval);
+            sign_bytes_detached_internal(&cert, &mut localdata, password)
+        })
+    }
 
-        Ok(true)
+    pub fn sign_bytes(
+        &self,
+        py: Python,
+        data: Vec<u8>,
+        password: KeyPassword,
+        armor: Option<bool>,
+    ) -> PyResult<PyObject> {
+        let cert = self.cert.clone();
+        let result = py.allow_threads(move || -> PyResult<_> {
+            let signers = get_signing_keys(&cert, password)?;
+            let mut result = Vec::new();
+            sign_internal(signers, &mut &data[..], &mut result, armor.unwrap_or(false))?;
+            Ok(result)
+        })?;
+        Ok(PyBytes::new(py, &result).into())
     }
 
-    pub fn decrypt_file(
+    pub fn sign_file(
         &self,
+        py: Python,
         filepath: Vec<u8>,
         output: Vec<u8>,
-        password: String,
+        password: KeyPassword,
+        armor: Option<bool>,
     ) -> PyResult<bool> {
-        let p = &NP::new();
+        let cert = self.cert.clone();
+        py.allow_threads(move || -> PyResult<bool> {
+            let signers = get_signing_keys(&cert, password)?;
+            let mut input = File::open(Path::new(str::from_utf8(&filepath[..])?))?;
+            let mut outfile = OutputFile::create(str::from_utf8(&output[..])?)?;
+            sign_internal(signers, &mut input, &mut outfile, armor.unwrap_or(false))?;
+            outfile.persist()?;
+            Ok(true)
+        })
+    }
 
-        let input = File::open(str::from_utf8(&filepath[..]).unwrap()).unwrap();
-        let mut outfile = File::create(str::from_utf8(&output[..]).unwrap()).unwrap();
+    pub fn verify_and_extract(
+        &self,
+        py: Python,
+        data: Vec<u8>,
+    ) -> PyResult<(PyObject, VerificationResult)> {
+        let cert = self.cert.clone();
+        let (result, verified) = py.allow_threads(move || -> PyResult<_> {
+            let mut result = Vec::new();
+            let verified = verify_internal(vec![cert], &data[..], &mut result)?;
+            Ok((result, verified))
+        })?;
+        Ok((PyBytes::new(py, &result).into(), verified))
+    }
 
-        let mut decryptor = DecryptorBuilder::from_reader(input)
-            .unwrap()
-            .with_policy(p, None, Helper::new(p, &self.cert, &password))
-            .unwrap();
-        std::io::copy(&mut decryptor, &mut outfile).unwrap();
-        Ok(true)
+    pub fn verify_and_extract_file(
+        &self,
+        py: Python,
+        filepath: Vec<u8>,
+        output: Vec<u8>,
+    ) -> PyResult<VerificationResult> {
+        let cert = self.cert.clone();
+        py.allow_threads(move || -> PyResult<VerificationResult> {
+            let input = File::open(Path::new(str::from_utf8(&filepath[..])?))?;
+            let mut outfile = OutputFile::create(str::from_utf8(&output[..])?)?;
+            let verified = verify_internal(vec![cert], input, &mut outfile)?;
+            // Without a good signature the content is not authenticated, the temporary file is
+            // removed when outfile is dropped
+            if verified.valid() {
+                outfile.persist()?;
+            }
+            Ok(verified)
+        })
     }
 
-    pub fn sign_bytes_detached(&self, data: Vec<u8>, password: String) -> PyResult<String> {
-        let mut localdata = io::Cursor::new(data);
-        sign_bytes_detached_internal(&self.cert, &mut localdata, password)
+    pub fn sign_cleartext(
+        &self,
+        py: Python,
+        data: Vec<u8>,
+        password: KeyPassword,
+    ) -> PyResult<String> {
+        let cert = self.cert.clone();
+        py.allow_threads(move || -> PyResult<String> {
+            sign_cleartext_internal(&cert, str::from_utf8(&data)?, password)
+        })
     }
 
-    pub fn sign_file_detached(&self, filepath: Vec<u8>, password: String) -> PyResult<String> {
-        let file = Path::new(str::from_utf8(&filepath[..]).unwrap());
-        let mut localdata = File::open(file).unwrap();
-        sign_bytes_detached_internal(&self.cert, &mut localdata, password)
+    pub fn verify_cleartext(
+        &self,
+        py: Python,
+        data: Vec<u8>,
+    ) -> PyResult<(String, VerificationResult)> {
+        let cert = self.cert.clone();
+        py.allow_threads(move || verify_cleartext_internal(&cert, &data))
     }
 
-    pub fn verify_bytes(&self, data: Vec<u8>, sig: Vec<u8>) -> PyResult<bool> {
-        let p = &P::new();
-        let vh = VHelper::new(&self.cert);
-        let mut v = DetachedVerifierBuilder::from_bytes(&sig[..])
-            .unwrap()
-            .with_policy(p, None, vh)
-            .unwrap();
-        match v.verify_bytes(data) {
-            Ok(()) => return Ok(true),
-            Err(_) => return Ok(false),
-        };
+    pub fn verify_bytes(
+        &self,
+        py: Python,
+        data: Vec<u8>,
+        sig: Vec<u8>,
+    ) -> PyResult<VerificationResult> {
+        let cert = self.cert.clone();
+        py.allow_threads(move || -> PyResult<VerificationResult> {
+            let p = &P::new();
+            let mut v = get_detached_verifier(p, vec![cert], &sig[..])?;
+            v.verify_bytes(data).map_err(to_pyerr)?;
+            Ok(v.into_helper().into())
+        })
     }
-    pub fn verify_file(&self, filepath: Vec<u8>, sig: Vec<u8>) -> PyResult<bool> {
-        let p = &P::new();
-        let vh = VHelper::new(&self.cert);
-        let mut v = DetachedVerifierBuilder::from_bytes(&sig[..])
-            .unwrap()
-            .with_policy(p, None, vh)
-            .unwrap();
-        let path = Path::new(str::from_utf8(&filepath[..]).unwrap());
-        match v.verify_file(path) {
-            Ok(()) => return Ok(true),
-            Err(_) => return Ok(false),
-        };
+
+    pub fn verify_file(
+        &self,
+        py: Python,
+        filepath: Vec<u8>,
+        sig: Vec<u8>,
+    ) -> PyResult<VerificationResult> {
+        let cert = self.cert.clone();
+        py.allow_threads(move || -> PyResult<VerificationResult> {
+            let p = &P::new();
+            let mut v = get_detached_verifier(p, vec![cert], &sig[..])?;
+            let path = Path::new(str::from_utf8(&filepath[..])?);
+            v.verify_file(path).map_err(to_pyerr)?;
+            Ok(v.into_helper().into())
+        })
     }
 }
 
 #[pymodule]
 /// A Python module implemented in Rust.
-fn johnnycanencrypt(_py: Python, m: &PyModule) -> PyResult<()> {
+fn johnnycanencrypt(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_wrapped(wrap_pyfunction!(create_newkey))?;
     m.add_wrapped(wrap_pyfunction!(parse_cert_file))?;
+    m.add_wrapped(wrap_pyfunction!(change_password))?;
+    m.add_wrapped(wrap_pyfunction!(revoke_cert))?;
+    m.add_wrapped(wrap_pyfunction!(apply_revocation))?;
+    m.add_wrapped(wrap_pyfunction!(add_userid))?;
+    m.add_wrapped(wrap_pyfunction!(revoke_userid))?;
+    m.add_wrapped(wrap_pyfunction!(set_primary_userid))?;
+    m.add_wrapped(wrap_pyfunction!(add_subkey))?;
+    m.add_wrapped(wrap_pyfunction!(set_subkey_expiration))?;
+    m.add_wrapped(wrap_pyfunction!(revoke_subkey))?;
+    m.add_wrapped(wrap_pyfunction!(set_expiration))?;
+    m.add_wrapped(wrap_pyfunction!(verify_bytes_detached))?;
+    m.add_wrapped(wrap_pyfunction!(verify_file_detached))?;
     m.add_wrapped(wrap_pyfunction!(encrypt_bytes_to_file))?;
     m.add_wrapped(wrap_pyfunction!(encrypt_bytes_to_bytes))?;
     m.add_wrapped(wrap_pyfunction!(encrypt_file_internal))?;
+    m.add_wrapped(wrap_pyfunction!(encrypt_and_sign_bytes))?;
+    m.add_wrapped(wrap_pyfunction!(encrypt_and_sign_file))?;
+    m.add_wrapped(wrap_pyfunction!(encrypt_bytes_with_password))?;
+    m.add_wrapped(wrap_pyfunction!(encrypt_file_with_password))?;
+    m.add_wrapped(wrap_pyfunction!(decrypt_bytes_with_password))?;
+    m.add_wrapped(wrap_pyfunction!(decrypt_file_with_password))?;
+    m.add_wrapped(wrap_pyfunction!(decrypt_bytes_with_keys))?;
+    m.add_wrapped(wrap_pyfunction!(decrypt_file_with_keys))?;
+    m.add_wrapped(wrap_pyfunction!(encrypt_stream))?;
+    m.add_wrapped(wrap_pyfunction!(decrypt_stream))?;
+    m.add_class::<Cert>()?;
+    m.add_class::<DecryptionResult>()?;
+    m.add_class::<EncryptionStream>()?;
+    m.add_class::<DecryptionStream>()?;
+    m.add_class::<VerificationResult>()?;
     m.add_class::<Johnny>()?;
+    m.add("CryptoError", py.get_type::<CryptoError>())?;
+    m.add("WrongPasswordError", py.get_type::<WrongPasswordError>())?;
+    m.add(
+        "NoDecryptionKeyError",
+        py.get_type::<NoDecryptionKeyError>(),
+    )?;
+    m.add(
+        "MalformedMessageError",
+        py.get_type::<MalformedMessageError>(),
+    )?;
+    m.add("KeyParseError", py.get_type::<KeyParseError>())?;
+    m.add("NoSigningKeyError", py.get_type::<NoSigningKeyError>())?;
+    m.add("PolicyRejectedError", py.get_type::<PolicyRejectedError>())?;
+    m.add("CancelledError", py.get_type::<CancelledError>())?;
     Ok(())
 }
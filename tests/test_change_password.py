@@ -0,0 +1,79 @@
+import os
+import tempfile
+import johnnycanencrypt as jce
+import pytest
+
+DATA = "Kushal loves 🦀"
+
+
+def setup_module(module):
+    module.tmpdirname = tempfile.TemporaryDirectory()
+
+
+def teardown_module(module):
+    del module.tmpdirname
+
+
+def test_change_password():
+    secret = jce.change_password("tests/files/secret.asc", "redhat", "fedora")
+    cert = jce.Cert(secret.encode("utf-8"))
+    assert cert.is_secret
+    details = cert.details()
+    assert details["secret_encrypted"]
+    assert all(sub["secret_encrypted"] for sub in details["subkeys"])
+
+    j = jce.Johnny(cert)
+    enc = j.encrypt_bytes(DATA.encode("utf-8"))
+    assert DATA == j.decrypt_bytes(enc, "fedora").plaintext.decode("utf-8")
+    with pytest.raises(jce.WrongPasswordError):
+        j.decrypt_bytes(enc, "redhat")
+    signature = j.sign_bytes_detached(DATA.encode("utf-8"), "fedora")
+    assert j.verify_bytes(DATA.encode("utf-8"), signature.encode("utf-8"))
+
+
+def test_remove_password():
+    secret = jce.change_password("tests/files/secret.asc", "redhat", None)
+    details = jce.Cert(secret.encode("utf-8")).details()
+    assert not details["secret_encrypted"]
+    assert not any(sub["secret_encrypted"] for sub in details["subkeys"])
+
+    # Now set a password again
+    secret = jce.change_password(jce.Cert(secret.encode("utf-8")), "", "redhat")
+    assert jce.Cert(secret.encode("utf-8")).details()["secret_encrypted"]
+
+
+def test_change_password_errors():
+    with pytest.raises(jce.WrongPasswordError):
+        jce.change_password("tests/files/secret.asc", "wrong password", "fedora")
+    with pytest.raises(jce.CryptoError):
+        jce.change_password("tests/files/public.asc", "redhat", "fedora")
+
+
+def test_keystore_change_password():
+    ks = jce.KeyStore(tmpdirname.name)
+    ks.import_cert("tests/files/public.asc")
+    ks.import_cert("tests/files/secret.asc")
+    fingerprint = "BB2D3F20233286371C3123D5209940B9669ED621"
+    key = ks.change_password(fingerprint, "redhat", "fedora")
+    assert key.keytype == "secret"
+    encrypted = ks.encrypt(fingerprint, DATA)
+    assert DATA == ks.decrypt(fingerprint, encrypted, "fedora").decode("utf-8")
+
+
+def test_keystore_change_password_failed_write(monkeypatch):
+    tmpdir = tempfile.TemporaryDirectory()
+    ks = jce.KeyStore(tmpdir.name)
+    key = ks.import_cert("tests/files/secret.asc")
+    with open(key.keypath) as fobj:
+        original = fobj.read()
+
+    def broken_replace(src, dst):
+        raise OSError("disk full")
+
+    monkeypatch.setattr(os, "replace", broken_replace)
+    with pytest.raises(OSError):
+        ks.change_password(key, "redhat", "fedora")
+    # The key file is untouched and the temporary file is removed
+    with open(key.keypath) as fobj:
+        assert fobj.read() == original
+    assert os.listdir(tmpdir.name) == [os.path.basename(key.keypath)]
@@ -1,4 +1,5 @@
 import os
+import pytest
 import johnnycanencrypt.johnnycanencrypt as jce
 
 DATA = "Kushal loves 🦀"
@@ -39,11 +40,11 @@ def test_encryption_of_multiple_keys_to_files():
         enc = f.read()
     jp = jce.Johnny("tests/files/hellosecret.asc")
     result = jp.decrypt_bytes(enc, "redhat")
-    assert DATA == result.decode("utf-8")
+    assert DATA == result.plaintext.decode("utf-8")
 
     jp = jce.Johnny("tests/files/secret.asc")
     result = jp.decrypt_bytes(enc, "redhat")
-    assert DATA == result.decode("utf-8")
+    assert DATA == result.plaintext.decode("utf-8")
 
 
 def test_encryption_of_multiple_keys_of_a_file():
@@ -88,11 +89,11 @@ def test_encryption_of_multiple_keys_to_bytes():
     # Now let us decrypt it via first secret key
     jp = jce.Johnny("tests/files/hellosecret.asc")
     result = jp.decrypt_bytes(encrypted, "redhat")
-    assert DATA == result.decode("utf-8")
+    assert DATA == result.plaintext.decode("utf-8")
 
     jp = jce.Johnny("tests/files/secret.asc")
     result = jp.decrypt_bytes(encrypted, "redhat")
-    assert DATA == result.decode("utf-8")
+    assert DATA == result.plaintext.decode("utf-8")
 
 
 def test_encrypt_decrypt_bytes():
@@ -101,7 +102,7 @@ def test_encrypt_decrypt_bytes():
     enc = j.encrypt_bytes(DATA.encode("utf-8"))
     jp = jce.Johnny("tests/files/secret.asc")
     result = jp.decrypt_bytes(enc, "redhat")
-    assert DATA == result.decode("utf-8")
+    assert DATA == result.plaintext.decode("utf-8")
 
 
 def test_encrypt_decrypt_bytes_armored():
@@ -111,7 +112,7 @@ def test_encrypt_decrypt_bytes_armored():
     assert enc.startswith(b"-----BEGIN PGP MESSAGE-----")
     jp = jce.Johnny("tests/files/secret.asc")
     result = jp.decrypt_bytes(enc, "redhat")
-    assert DATA == result.decode("utf-8")
+    assert DATA == result.plaintext.decode("utf-8")
 
 
 def test_encrypt_decrypt_files():
@@ -189,5 +190,370 @@ def test_decrypt_multiple_recipient_data():
         data = f.read()
 
     jp = jce.Johnny("tests/files/secret.asc")
-    cleartext = jp.decrypt_bytes(data, "redhat")
-    assert cleartext == b"Hello World! for 2.\n"
+    result = jp.decrypt_bytes(data, "redhat")
+    assert result.plaintext == b"Hello World! for 2.\n"
+
+
+def test_encrypt_decrypt_bytes_with_password():
+    "Encrypts only using a password, no public key"
+    enc = jce.encrypt_bytes_with_password(DATA.encode("utf-8"), "redhat", armor=True)
+    assert enc.startswith(b"-----BEGIN PGP MESSAGE-----")
+    result = jce.decrypt_bytes_with_password(enc, "redhat")
+    assert DATA == result.plaintext.decode("utf-8")
+
+
+def test_decrypt_bytes_with_wrong_password():
+    enc = jce.encrypt_bytes_with_password(DATA.encode("utf-8"), "redhat")
+    with pytest.raises(jce.WrongPasswordError):
+        jce.decrypt_bytes_with_password(enc, "fedora")
+
+
+def test_encrypt_decrypt_files_with_password():
+    inputfile = "tests/files/text.txt"
+    output = "/tmp/text-encrypted.asc"
+    decrypted_output = "/tmp/text.txt"
+    clean_outputfiles(output, decrypted_output)
+
+    assert jce.encrypt_file_with_password(
+        inputfile.encode("utf-8"), output.encode("utf-8"), "redhat", armor=True
+    )
+    assert jce.decrypt_file_with_password(
+        output.encode("utf-8"), decrypted_output.encode("utf-8"), "redhat"
+    )
+    verify_files(inputfile, decrypted_output)
+
+
+def test_decrypt_bytes_falls_back_to_passphrase():
+    "The secret key can not decrypt, so the passphrase is used"
+    enc = jce.encrypt_bytes_with_password(DATA.encode("utf-8"), "mypassphrase")
+    jp = jce.Johnny("tests/files/secret.asc")
+    result = jp.decrypt_bytes(enc, "redhat", passphrase="mypassphrase")
+    assert DATA == result.plaintext.decode("utf-8")
+
+
+def test_encrypt_and_sign_bytes():
+    "Signs and encrypts in one pass, then verifies while decrypting"
+    enc = jce.encrypt_and_sign_bytes(
+        ["tests/files/public.asc"],
+        "tests/files/hellosecret.asc",
+        "redhat",
+        DATA.encode("utf-8"),
+        armor=True,
+    )
+    jp = jce.Johnny("tests/files/secret.asc")
+    # Without signers nothing can be verified
+    result = jp.decrypt_bytes(enc, "redhat")
+    assert DATA == result.plaintext.decode("utf-8")
+    assert result.verified == []
+    # An unknown signer is not a bad signature
+    assert result.bad_signatures == 0
+    assert not result.verification
+    assert [s["status"] for s in result.verification.signatures] == ["unknown_key"]
+    result = jp.decrypt_bytes(enc, "redhat", signers=["tests/files/hellopublic.asc"])
+    assert DATA == result.plaintext.decode("utf-8")
+    assert result.verified == ["6AC6957E2589CB8B5221F6508ADA07F0A0F7BA99"]
+    assert result.verification
+    assert [s["status"] for s in result.verification.signatures] == ["good"]
+    # Signed by a different key
+    result = jp.decrypt_bytes(enc, "redhat", signers=["tests/files/public.asc"])
+    assert DATA == result.plaintext.decode("utf-8")
+    assert result.verified == []
+    assert result.bad_signatures == 0
+
+
+def test_decrypt_weak_hash_signature():
+    "A signature over SHA-1 is rejected by the standard policy, so it is not good"
+    with open("tests/files/sha1_signed.asc", "rb") as fobj:
+        enc = fobj.read()
+    jp = jce.Johnny("tests/files/secret.asc")
+    result = jp.decrypt_bytes(enc, "redhat", signers=["tests/files/public.asc"])
+    assert DATA == result.plaintext.decode("utf-8")
+    assert result.verified == []
+    assert not result.verification
+    (signature,) = result.verification.signatures
+    assert signature["hash_algo"] == "SHA1"
+    assert not signature["good"]
+
+
+def test_encrypt_and_sign_file():
+    inputfile = "tests/files/text.txt"
+    output = "/tmp/text-encrypted.asc"
+    decrypted_output = "/tmp/text.txt"
+    clean_outputfiles(output, decrypted_output)
+
+    assert jce.encrypt_and_sign_file(
+        ["tests/files/public.asc"],
+        "tests/files/hellosecret.asc",
+        "redhat",
+        inputfile.encode("utf-8"),
+        output.encode("utf-8"),
+        armor=True,
+    )
+    jp = jce.Johnny("tests/files/secret.asc")
+    result = jp.decrypt_file(
+        output.encode("utf-8"),
+        decrypted_output.encode("utf-8"),
+        "redhat",
+        signers=["tests/files/hellopublic.asc"],
+    )
+    assert result.verified == ["6AC6957E2589CB8B5221F6508ADA07F0A0F7BA99"]
+    verify_files(inputfile, decrypted_output)
+
+
+def test_decryption_result():
+    "Checks the details of the decrypted message"
+    j = jce.Johnny("tests/files/public.asc")
+    enc = j.encrypt_bytes(DATA.encode("utf-8"), armor=True)
+    jp = jce.Johnny("tests/files/secret.asc")
+    result = jp.decrypt_bytes(enc, "redhat")
+    assert DATA == result.plaintext.decode("utf-8")
+    assert result.output is None
+    # The encryption subkey of the key
+    assert result.fingerprint == "9EBFCA465490663C22AEF1443CE170115CF4322E"
+    assert result.recipients == ["3CE170115CF4322E"]
+    assert result.sym_algo
+    assert result.format
+    assert result.verified == []
+    assert result.bad_signatures == 0
+    assert result.verification.signatures == []
+
+
+def test_decryption_result_multiple_recipients():
+    with open("tests/files/double_recipient.asc", "rb") as f:
+        data = f.read()
+
+    jp = jce.Johnny("tests/files/secret.asc")
+    result = jp.decrypt_bytes(data, "redhat")
+    assert sorted(result.recipients) == ["3CE170115CF4322E", "76E7E83323D9A3AF"]
+    assert result.fingerprint == "9EBFCA465490663C22AEF1443CE170115CF4322E"
+
+
+def test_decryption_result_file():
+    inputfile = "tests/files/text.txt"
+    output = "/tmp/text-encrypted.pgp"
+    decrypted_output = "/tmp/text.txt"
+    clean_outputfiles(output, decrypted_output)
+
+    j = jce.Johnny("tests/files/public.asc")
+    assert j.encrypt_file(inputfile.encode("utf-8"), output.encode("utf-8"))
+    jp = jce.Johnny("tests/files/secret.asc")
+    result = jp.decrypt_file(
+        output.encode("utf-8"), decrypted_output.encode("utf-8"), "redhat"
+    )
+    assert result.plaintext is None
+    assert result.output == decrypted_output
+    verify_files(inputfile, decrypted_output)
+
+
+def test_decryption_result_password():
+    enc = jce.encrypt_bytes_with_password(DATA.encode("utf-8"), "redhat")
+    result = jce.decrypt_bytes_with_password(enc, "redhat")
+    assert result.fingerprint is None
+    assert result.recipients == []
+    assert result.sym_algo
+
+
+def test_decrypt_bytes_with_keys():
+    "The secret key is selected based on the recipients of the message"
+    j = jce.Johnny("tests/files/hellopublic.asc")
+    enc = j.encrypt_bytes(DATA.encode("utf-8"), armor=True)
+    secretkeys = ["tests/files/secret.asc", "tests/files/hellosecret.asc"]
+    result = jce.decrypt_bytes_with_keys(secretkeys, enc, "redhat")
+    assert DATA == result.plaintext.decode("utf-8")
+    assert result.recipients == ["76E7E83323D9A3AF"]
+    # The password of the other key is never used
+    passwords = {
+        "6ac6957e2589cb8b5221f6508ada07f0a0f7ba99": "redhat",
+        "BB2D3F20233286371C3123D5209940B9669ED621": "wrongpassword",
+    }
+    result = jce.decrypt_bytes_with_keys(secretkeys, enc, passwords)
+    assert DATA == result.plaintext.decode("utf-8")
+    with pytest.raises(jce.WrongPasswordError):
+        jce.decrypt_bytes_with_keys(secretkeys, enc, "wrongpassword")
+    with pytest.raises(jce.NoDecryptionKeyError):
+        jce.decrypt_bytes_with_keys(["tests/files/secret.asc"], enc, "redhat")
+    with pytest.raises(jce.NoDecryptionKeyError):
+        jce.decrypt_bytes_with_keys(["tests/files/public.asc"], enc, "redhat")
+
+
+def test_decrypt_file_with_keys():
+    inputfile = "tests/files/text.txt"
+    output = "/tmp/text-encrypted.pgp"
+    decrypted_output = "/tmp/text.txt"
+    clean_outputfiles(output, decrypted_output)
+
+    j = jce.Johnny("tests/files/public.asc")
+    assert j.encrypt_file(inputfile.encode("utf-8"), output.encode("utf-8"))
+    result = jce.decrypt_file_with_keys(
+        ["tests/files/hellosecret.asc", "tests/files/secret.asc"],
+        output.encode("utf-8"),
+        decrypted_output.encode("utf-8"),
+        {"BB2D3F20233286371C3123D5209940B9669ED621": "redhat"},
+    )
+    assert result.fingerprint == "9EBFCA465490663C22AEF1443CE170115CF4322E"
+    verify_files(inputfile, decrypted_output)
+
+
+def test_encrypt_decrypt_bytes_wildcard():
+    "The recipients are hidden in the message"
+    enc = jce.encrypt_bytes_to_bytes(
+        ["tests/files/public.asc"], DATA.encode("utf-8"), armor=True, wildcard=True
+    )
+    jp = jce.Johnny("tests/files/secret.asc")
+    result = jp.decrypt_bytes(enc, "redhat")
+    assert DATA == result.plaintext.decode("utf-8")
+    assert result.recipients == ["0000000000000000"]
+    assert result.fingerprint == "9EBFCA465490663C22AEF1443CE170115CF4322E"
+    # Any of the secret keys can be tried
+    secretkeys = ["tests/files/hellosecret.asc", "tests/files/secret.asc"]
+    result = jce.decrypt_bytes_with_keys(secretkeys, enc, "redhat")
+    assert DATA == result.plaintext.decode("utf-8")
+    jp = jce.Johnny("tests/files/hellosecret.asc")
+    with pytest.raises(jce.NoDecryptionKeyError):
+        jp.decrypt_bytes(enc, "redhat")
+
+
+def test_encrypt_decrypt_file_wildcard():
+    inputfile = "tests/files/text.txt"
+    output = "/tmp/text-encrypted.pgp"
+    decrypted_output = "/tmp/text.txt"
+    clean_outputfiles(output, decrypted_output)
+
+    j = jce.Johnny("tests/files/public.asc")
+    assert j.encrypt_file(
+        inputfile.encode("utf-8"), output.encode("utf-8"), wildcard=True
+    )
+    jp = jce.Johnny("tests/files/secret.asc")
+    result = jp.decrypt_file(
+        output.encode("utf-8"), decrypted_output.encode("utf-8"), "redhat"
+    )
+    assert result.recipients == ["0000000000000000"]
+    verify_files(inputfile, decrypted_output)
+
+
+def test_encrypt_decrypt_bytes_cipher():
+    "The symmetric algorithm can be chosen, else the preferred one is used"
+    data = DATA.encode("utf-8")
+    jp = jce.Johnny("tests/files/secret.asc")
+    enc = jce.encrypt_bytes_to_bytes(["tests/files/public.asc"], data, cipher="aes128")
+    result = jp.decrypt_bytes(enc, "redhat")
+    assert DATA == result.plaintext.decode("utf-8")
+    assert result.sym_algo == "AES128"
+    enc = jce.encrypt_bytes_to_bytes(["tests/files/public.asc"], data)
+    assert jp.decrypt_bytes(enc, "redhat").sym_algo == "AES256"
+    with pytest.raises(ValueError):
+        jce.encrypt_bytes_to_bytes(["tests/files/public.asc"], data, cipher="rot13")
+    with pytest.raises(jce.PolicyRejectedError):
+        jce.encrypt_bytes_to_bytes(["tests/files/public.asc"], data, cipher="3des")
+
+
+def test_encrypt_decrypt_bytes_compression():
+    data = DATA.encode("utf-8") * 1000
+    jp = jce.Johnny("tests/files/secret.asc")
+    plain = jce.encrypt_bytes_to_bytes(["tests/files/public.asc"], data)
+    for compression in ["zip", "zlib", "bzip2", ("zlib", 9)]:
+        enc = jce.encrypt_bytes_to_bytes(
+            ["tests/files/public.asc"], data, compression=compression
+        )
+        assert len(enc) < len(plain)
+        assert data == jp.decrypt_bytes(enc, "redhat").plaintext
+    enc = jce.encrypt_bytes_to_bytes(
+        ["tests/files/public.asc"], data, compression="none"
+    )
+    assert data == jp.decrypt_bytes(enc, "redhat").plaintext
+    with pytest.raises(ValueError):
+        jce.encrypt_bytes_to_bytes(["tests/files/public.asc"], data, compression="lz4")
+    with pytest.raises(ValueError):
+        jce.encrypt_bytes_to_bytes(
+            ["tests/files/public.asc"], data, compression=("zlib", 10)
+        )
+
+
+def test_encrypt_decrypt_aead():
+    data = DATA.encode("utf-8")
+    enc = jce.encrypt_bytes_with_password(data, "redhat", aead="eax")
+    assert data == jce.decrypt_bytes_with_password(enc, "redhat").plaintext
+    with pytest.raises(ValueError):
+        jce.encrypt_bytes_with_password(data, "redhat", aead="gcm")
+    # The test keys only advertise the MDC feature, not AEAD
+    with pytest.raises(jce.CryptoError):
+        jce.encrypt_bytes_to_bytes(["tests/files/public.asc"], data, aead="eax")
+
+
+def test_encrypt_decrypt_in_threads():
+    "The GIL is released, and taken back for the password callback"
+    from concurrent.futures import ThreadPoolExecutor
+
+    def ask_password(fingerprint, uid, attempt):
+        return "redhat"
+
+    def roundtrip(i):
+        data = f"{DATA} {i}".encode("utf-8")
+        enc = jce.encrypt_bytes_to_bytes(["tests/files/public.asc"], data)
+        result = jce.decrypt_bytes_with_keys(
+            ["tests/files/secret.asc"], enc, ask_password
+        )
+        return result.plaintext == data
+
+    with ThreadPoolExecutor(max_workers=4) as executor:
+        assert all(executor.map(roundtrip, range(16)))
+
+
+def test_decrypt_file_manipulated_message():
+    "A failed decryption never leaves any partial plain text"
+    import tempfile
+
+    with tempfile.TemporaryDirectory() as tmpdirname:
+        encrypted = os.path.join(tmpdirname, "text.txt.pgp")
+        decrypted = os.path.join(tmpdirname, "text.txt")
+        jce.encrypt_file_internal(
+            ["tests/files/public.asc"], b"tests/files/text.txt", encrypted.encode()
+        )
+        with open(encrypted, "rb") as fobj:
+            data = bytearray(fobj.read())
+        # Modify the literal data, the MDC check at the end fails
+        data[-30] ^= 0xFF
+        with open(encrypted, "wb") as fobj:
+            fobj.write(data)
+        with open(decrypted, "w") as fobj:
+            fobj.write("old content")
+
+        jp = jce.Johnny("tests/files/secret.asc")
+        with pytest.raises(jce.CryptoError):
+            jp.decrypt_file(encrypted.encode(), decrypted.encode(), "redhat")
+        # The existing output is not touched, and the temporary file is removed
+        with open(decrypted) as fobj:
+            assert fobj.read() == "old content"
+        assert sorted(os.listdir(tmpdirname)) == ["text.txt", "text.txt.pgp"]
+
+
+def test_gil_released_while_generating_key():
+    "Another Python thread keeps running while the key is generated"
+    import sys
+    import threading
+    import time
+
+    stop = threading.Event()
+    counter = [0]
+
+    def count():
+        while not stop.is_set():
+            counter[0] += 1
+            # Gives the GIL back, so that the main thread can take it again
+            time.sleep(0)
+
+    # The main thread is never forced to switch, so the counter thread can only run
+    # when the main thread releases the GIL
+    interval = sys.getswitchinterval()
+    sys.setswitchinterval(1000)
+    thread = threading.Thread(target=count)
+    try:
+        thread.start()
+        before = counter[0]
+        jce.create_newkey("redhat", "GIL <gil@example.com>", "Cv25519")
+        after = counter[0]
+    finally:
+        stop.set()
+        thread.join()
+        sys.setswitchinterval(interval)
+    assert after > before
@@ -0,0 +1,61 @@
+import os
+import tempfile
+
+import pytest
+import johnnycanencrypt as jce
+
+DATA = "Kushal loves 🦀"
+
+
+def test_exception_hierarchy():
+    for exc in [
+        jce.WrongPasswordError,
+        jce.NoDecryptionKeyError,
+        jce.MalformedMessageError,
+        jce.KeyParseError,
+        jce.NoSigningKeyError,
+        jce.PolicyRejectedError,
+    ]:
+        assert issubclass(exc, jce.CryptoError)
+
+
+def test_decrypt_wrong_password():
+    j = jce.Johnny("tests/files/public.asc")
+    enc = j.encrypt_bytes(DATA.encode("utf-8"), armor=True)
+    jp = jce.Johnny("tests/files/secret.asc")
+    with pytest.raises(jce.WrongPasswordError):
+        jp.decrypt_bytes(enc, "wrongpassword")
+
+
+def test_decrypt_wrong_key():
+    j = jce.Johnny("tests/files/public.asc")
+    enc = j.encrypt_bytes(DATA.encode("utf-8"), armor=True)
+    jp = jce.Johnny("tests/files/hellosecret.asc")
+    with pytest.raises(jce.NoDecryptionKeyError):
+        jp.decrypt_bytes(enc, "redhat")
+
+
+def test_decrypt_malformed_message():
+    jp = jce.Johnny("tests/files/secret.asc")
+    with pytest.raises(jce.MalformedMessageError):
+        jp.decrypt_bytes(b"This is not an OpenPGP message.", "redhat")
+
+
+def test_sign_wrong_password():
+    j = jce.Johnny("tests/files/secret.asc")
+    with pytest.raises(jce.WrongPasswordError):
+        j.sign_bytes_detached(DATA.encode("utf-8"), "wrongpassword")
+
+
+def test_parse_bad_key():
+    with tempfile.TemporaryDirectory() as tmpdir:
+        keypath = os.path.join(tmpdir, "bad.asc")
+        with open(keypath, "w") as fobj:
+            fobj.write("This is not a key.")
+        with pytest.raises(jce.KeyParseError):
+            jce.parse_cert_file(keypath)
+        with pytest.raises(jce.KeyParseError):
+            jce.Johnny(keypath)
+        # The keystore should skip the bad file
+        ks = jce.KeyStore(tmpdir)
+        assert (0, 0) == ks.details()
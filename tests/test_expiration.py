@@ -0,0 +1,61 @@
+import time
+import tempfile
+import johnnycanencrypt as jce
+import pytest
+
+DATA = "Kushal loves 🦀"
+
+
+def setup_module(module):
+    module.tmpdirname = tempfile.TemporaryDirectory()
+
+
+def teardown_module(module):
+    del module.tmpdirname
+
+
+def test_set_expiration():
+    when = int(time.time()) + 2 * 365 * 24 * 60 * 60
+    public, secret = jce.set_expiration("tests/files/secret.asc", "redhat", when)
+    details = jce.Cert(public.encode("utf-8")).details()
+    assert details["expiration_time"] == when
+    # The subkeys are not changed
+    assert all(sub["expiration_time"] != when for sub in details["subkeys"])
+
+    # Now remove the expiration
+    public, _ = jce.set_expiration(jce.Cert(secret.encode("utf-8")), "redhat", None)
+    assert jce.Cert(public.encode("utf-8")).details()["expiration_time"] is None
+
+
+def test_set_expiration_subkeys():
+    when = int(time.time()) + 3600
+    public, _ = jce.set_expiration(
+        "tests/files/secret.asc", "redhat", when, subkeys=True
+    )
+    details = jce.Cert(public.encode("utf-8")).details()
+    assert details["expiration_time"] == when
+    assert all(sub["expiration_time"] == when for sub in details["subkeys"])
+
+
+def test_set_expiration_errors():
+    with pytest.raises(ValueError):
+        jce.set_expiration("tests/files/secret.asc", "redhat", 0)
+    with pytest.raises(jce.WrongPasswordError):
+        jce.set_expiration("tests/files/secret.asc", "wrong password", None)
+
+
+def test_extend_expired_key():
+    now = int(time.time())
+    ks = jce.KeyStore(tmpdirname.name)
+    key = ks.create_newkey(
+        "redhat",
+        "test key <test@example.com>",
+        "Cv25519",
+        creation_time=now - 3 * 365 * 24 * 60 * 60,
+        expiration=2 * 365 * 24 * 60 * 60,
+    )
+    with pytest.raises(jce.CryptoError):
+        ks.encrypt(key, DATA)
+    ks.set_expiration(key, "redhat", now + 365 * 24 * 60 * 60, subkeys=True)
+    encrypted = ks.encrypt(key, DATA)
+    assert DATA == ks.decrypt(key.fingerprint, encrypted, "redhat").decode("utf-8")
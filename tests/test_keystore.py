@@ -1,5 +1,6 @@
 import os
 import shutil
+import stat
 import tempfile
 import johnnycanencrypt as jce
 import pytest
@@ -245,6 +246,25 @@ def test_ks_encrypt_decrypt_file_multiple_recipients(encrypt_decrypt_file):
     verify_files(inputfile, decrypted_output)
 
 
+def test_ks_decrypt_with_all_keys():
+    "Decrypts with the secret key selected from the store"
+    ks = jce.KeyStore("tests/files/store")
+    key = ks.get_key("BB2D3F20233286371C3123D5209940B9669ED621")
+    encrypted = ks.encrypt(key, DATA)
+    assert DATA.encode("utf-8") == ks.decrypt(None, encrypted, password="redhat")
+    # Only the password of the key which can decrypt the message is used
+    passwords = {
+        "BB2D3F20233286371C3123D5209940B9669ED621": "redhat",
+        "6AC6957E2589CB8B5221F6508ADA07F0A0F7BA99": "wrongpassword",
+    }
+    assert DATA.encode("utf-8") == ks.decrypt(None, encrypted, password=passwords)
+    keys = ["6AC6957E2589CB8B5221F6508ADA07F0A0F7BA99"]
+    with pytest.raises(jce.NoDecryptionKeyError):
+        ks.decrypt(keys, encrypted, password="redhat")
+    with pytest.raises(jce.WrongPasswordError):
+        ks.decrypt(None, encrypted, password="wrongpassword")
+
+
 def test_ks_sign_data():
     ks = jce.KeyStore("tests/files/store")
     key = "6AC6957E2589CB8B5221F6508ADA07F0A0F7BA99"
@@ -261,6 +281,18 @@ def test_ks_sign_data_fails():
     assert not ks.verify(key, "hello2", signed)
 
 
+def test_ks_verify_with_all_keys():
+    ks = jce.KeyStore("tests/files/store")
+    key = "6AC6957E2589CB8B5221F6508ADA07F0A0F7BA99"
+    signed = ks.sign(key, "hello", "redhat")
+    result = ks.verify(None, "hello", signed)
+    assert result.verified == [key]
+    keys = [key, "BB2D3F20233286371C3123D5209940B9669ED621"]
+    result = ks.verify(keys, "hello", signed)
+    assert result.has_good_signers(1)
+    assert not result.has_good_signers(2)
+
+
 def test_ks_sign_verify_file():
     inputfile = "tests/files/text.txt"
     tempdir = tempfile.TemporaryDirectory()
@@ -271,3 +303,72 @@ def test_ks_sign_verify_file():
     signed = ks.sign_file(key, file_to_be_signed, "redhat", write=True)
     assert signed.startswith("-----BEGIN PGP SIGNATURE-----\n")
     assert ks.verify_file(key, file_to_be_signed, file_to_be_signed + ".asc")
+
+
+def test_create_newkey_options():
+    "Creates a key without password, with multiple uids and expiration"
+    public, secret, fingerprint, _ = jce.create_newkey(
+        None,
+        ["test key <test@example.com>", "test key <test@example.org>"],
+        "Cv25519",
+        expiration=3600,
+        encryption="both",
+        authentication=True,
+    )
+    cert = jce.Cert(secret.encode("utf-8"))
+    assert cert.fingerprint == fingerprint
+    details = cert.details()
+    assert details["algorithm"] == "EdDSA"
+    assert details["expiration_time"] == details["creation_time"] + 3600
+    assert not details["secret_encrypted"]
+    assert [uid["email"] for uid in details["uids"]] == [
+        "test@example.com",
+        "test@example.org",
+    ]
+    subkeys = details["subkeys"]
+    assert len(subkeys) == 3
+    assert any(
+        sub["encrypt_storage"] and sub["encrypt_transport"] for sub in subkeys
+    )
+    assert any(sub["sign"] for sub in subkeys)
+    assert any(sub["authenticate"] for sub in subkeys)
+
+    # Without password the data can be signed and decrypted
+    j = jce.Johnny(cert)
+    enc = j.encrypt_bytes(DATA.encode("utf-8"))
+    assert DATA == j.decrypt_bytes(enc, "").plaintext.decode("utf-8")
+
+
+def test_create_newkey_rsa2k():
+    _, secret, _, _ = jce.create_newkey("redhat", "test key", "RSA2k", signing=False)
+    details = jce.Cert(secret.encode("utf-8")).details()
+    assert details["bits"] == 2048
+    assert len(details["subkeys"]) == 1
+
+
+def test_create_newkey_unknown_cipher():
+    with pytest.raises(ValueError):
+        jce.create_newkey("redhat", "test key", "RSA1k")
+
+
+def test_ks_file_modes():
+    "The secret keys are only readable by the owner, the other files keep their mode"
+    tempdir = tempfile.TemporaryDirectory()
+    ks = jce.KeyStore(tempdir.name)
+    key = ks.create_newkey("redhat", "test key <test@example.com>", "Cv25519")
+    umask = os.umask(0)
+    os.umask(umask)
+
+    def mode(extension):
+        path = os.path.join(tempdir.name, key.fingerprint + extension)
+        return stat.S_IMODE(os.stat(path).st_mode)
+
+    assert mode(".pub") == 0o666 & ~umask
+    assert mode(".rev") == 0o666 & ~umask
+    assert mode(".sec") == 0o600
+
+    # The updated public key keeps the mode of the existing file
+    os.chmod(key.keypath, 0o640)
+    ks.add_userid(key, "test key <test@example.org>", "redhat")
+    assert mode(".pub") == 0o640
+    assert mode(".sec") == 0o600
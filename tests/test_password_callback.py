@@ -0,0 +1,114 @@
+import pytest
+import johnnycanencrypt as jce
+
+DATA = "Kushal loves 🦀"
+
+
+class PasswordAsker:
+    "Returns the passwords in order, and remembers the calls"
+
+    def __init__(self, *passwords):
+        self.passwords = list(passwords)
+        self.calls = []
+
+    def __call__(self, fingerprint, uid, attempt):
+        self.calls.append((fingerprint, uid, attempt))
+        if not self.passwords:
+            return None
+        return self.passwords.pop(0)
+
+
+def encrypted_data():
+    j = jce.Johnny("tests/files/public.asc")
+    return j.encrypt_bytes(DATA.encode("utf-8"), armor=True)
+
+
+def test_decrypt_with_callback():
+    asker = PasswordAsker("redhat")
+    jp = jce.Johnny("tests/files/secret.asc")
+    result = jp.decrypt_bytes(encrypted_data(), asker)
+    assert DATA == result.plaintext.decode("utf-8")
+    # Only asked for the encryption subkey
+    assert len(asker.calls) == 1
+    fingerprint, uid, attempt = asker.calls[0]
+    assert fingerprint == "9EBFCA465490663C22AEF1443CE170115CF4322E"
+    assert uid
+    assert attempt == 0
+
+
+def test_decrypt_with_callback_retries():
+    asker = PasswordAsker("wrongpassword", "redhat")
+    jp = jce.Johnny("tests/files/secret.asc")
+    result = jp.decrypt_bytes(encrypted_data(), asker)
+    assert DATA == result.plaintext.decode("utf-8")
+    assert [call[2] for call in asker.calls] == [0, 1]
+
+
+def test_decrypt_with_callback_gives_up():
+    asker = PasswordAsker("wrongpassword")
+    jp = jce.Johnny("tests/files/secret.asc")
+    with pytest.raises(jce.WrongPasswordError):
+        jp.decrypt_bytes(encrypted_data(), asker)
+    assert len(asker.calls) == 2
+    asker = PasswordAsker("wrong1", "wrong2", "wrong3", "redhat")
+    with pytest.raises(jce.WrongPasswordError):
+        jp.decrypt_bytes(encrypted_data(), asker)
+    assert len(asker.calls) == 3
+
+
+def test_decrypt_with_callback_only_for_the_needed_key():
+    asker = PasswordAsker("redhat")
+    secretkeys = ["tests/files/hellosecret.asc", "tests/files/secret.asc"]
+    result = jce.decrypt_bytes_with_keys(secretkeys, encrypted_data(), asker)
+    assert DATA == result.plaintext.decode("utf-8")
+    assert [call[0] for call in asker.calls] == [
+        "9EBFCA465490663C22AEF1443CE170115CF4322E"
+    ]
+
+
+def test_wildcard_callback_only_for_encryption_keys():
+    "The signing (sub)keys are never tried for the hidden recipients"
+    j = jce.Johnny("tests/files/public.asc")
+    enc = j.encrypt_bytes(DATA.encode("utf-8"), wildcard=True)
+    asker = PasswordAsker("redhat", "redhat", "redhat")
+    result = jce.decrypt_bytes_with_keys(["tests/files/secret.asc"], enc, asker)
+    assert DATA == result.plaintext.decode("utf-8")
+    assert [call[0] for call in asker.calls] == [
+        "9EBFCA465490663C22AEF1443CE170115CF4322E"
+    ]
+
+
+class Cancelled(Exception):
+    pass
+
+
+def test_callback_error_is_raised():
+    def asker(fingerprint, uid, attempt):
+        raise Cancelled()
+
+    jp = jce.Johnny("tests/files/secret.asc")
+    with pytest.raises(Cancelled):
+        jp.decrypt_bytes(encrypted_data(), asker)
+    with pytest.raises(Cancelled):
+        jp.sign_bytes_detached(DATA.encode("utf-8"), asker)
+
+
+def test_sign_with_callback():
+    asker = PasswordAsker("redhat")
+    j = jce.Johnny("tests/files/secret.asc")
+    signature = j.sign_bytes_detached(DATA.encode("utf-8"), asker)
+    assert len(asker.calls) == 1
+    jp = jce.Johnny("tests/files/public.asc")
+    assert jp.verify_bytes(DATA.encode("utf-8"), signature.encode("utf-8"))
+    with pytest.raises(jce.WrongPasswordError):
+        j.sign_bytes_detached(DATA.encode("utf-8"), PasswordAsker())
+
+
+def test_keystore_with_callback():
+    ks = jce.KeyStore("tests/files/store")
+    key = "BB2D3F20233286371C3123D5209940B9669ED621"
+    encrypted = ks.encrypt(key, DATA)
+    decrypted = ks.decrypt(None, encrypted, password=PasswordAsker("redhat"))
+    assert DATA.encode("utf-8") == decrypted
+    signature = ks.sign(key, DATA, PasswordAsker("redhat"))
+    assert ks.verify(key, DATA, signature)
@@ -0,0 +1,122 @@
+import os
+import tempfile
+
+import pytest
+import johnnycanencrypt as jce
+
+KEY = "BB2D3F20233286371C3123D5209940B9669ED621"
+
+
+class Progress:
+    "Remembers the calls, and cancels after the given number of calls"
+
+    def __init__(self, cancel_after=None):
+        self.calls = []
+        self.cancel_after = cancel_after
+
+    def __call__(self, done, total):
+        self.calls.append((done, total))
+        if self.cancel_after is not None and len(self.calls) >= self.cancel_after:
+            return False
+
+
+def setup_module(module):
+    module.tmpdirname = tempfile.TemporaryDirectory()
+    module.inputfile = os.path.join(module.tmpdirname.name, "data.bin")
+    with open(module.inputfile, "wb") as fobj:
+        fobj.write(os.urandom(100 * 1024))
+
+
+def teardown_module(module):
+    del module.tmpdirname
+
+
+def check_calls(calls, total):
+    assert len(calls) > 1
+    assert [done for done, _ in calls] == sorted(done for done, _ in calls)
+    assert all(t == total for _, t in calls)
+    # The last call is always at the end of the input
+    assert calls[-1] == (total, total)
+
+
+def test_encrypt_decrypt_file_progress():
+    ks = jce.KeyStore("tests/files/store")
+    encrypted = os.path.join(tmpdirname.name, "data.bin.pgp")
+    decrypted = os.path.join(tmpdirname.name, "data.bin.out")
+    progress = Progress()
+    assert ks.encrypt_file(
+        KEY, inputfile, encrypted, progress=progress, progress_interval=10000
+    )
+    check_calls(progress.calls, os.path.getsize(inputfile))
+
+    progress = Progress()
+    assert ks.decrypt_file(
+        KEY,
+        encrypted,
+        decrypted,
+        password="redhat",
+        progress=progress,
+        progress_interval=10000,
+    )
+    check_calls(progress.calls, os.path.getsize(encrypted))
+    with open(inputfile, "rb") as f1, open(decrypted, "rb") as f2:
+        assert f1.read() == f2.read()
+
+    # By default it is only called at the end for the small files
+    progress = Progress()
+    assert ks.decrypt_file(None, encrypted, decrypted, "redhat", progress=progress)
+    assert progress.calls == [(os.path.getsize(encrypted),) * 2]
+
+
+def test_cancel_encrypt_decrypt_file():
+    ks = jce.KeyStore("tests/files/store")
+    encrypted = os.path.join(tmpdirname.name, "cancelled.pgp")
+    decrypted = os.path.join(tmpdirname.name, "cancelled.out")
+    with pytest.raises(jce.CancelledError):
+        ks.encrypt_file(
+            KEY, inputfile, encrypted, progress=Progress(2), progress_interval=10000
+        )
+    # The partial output is removed
+    assert not os.path.exists(encrypted)
+
+    assert ks.encrypt_file(KEY, inputfile, encrypted)
+    with pytest.raises(jce.CancelledError):
+        ks.decrypt_file(
+            KEY,
+            encrypted,
+            decrypted,
+            password="redhat",
+            progress=Progress(2),
+            progress_interval=10000,
+        )
+    assert not os.path.exists(decrypted)
+
+
+def test_sign_file_progress():
+    ks = jce.KeyStore("tests/files/store")
+    progress = Progress()
+    ks.sign_file(
+        KEY,
+        inputfile,
+        "redhat",
+        write=True,
+        progress=progress,
+        progress_interval=10000,
+    )
+    check_calls(progress.calls, os.path.getsize(inputfile))
+    assert ks.verify_file(KEY, inputfile, inputfile + ".asc")
+
+    class Stop(Exception):
+        pass
+
+    def broken(done, total):
+        raise Stop()
+
+    # Any exception from the callback stops the operation
+    with pytest.raises(Stop):
+        ks.sign_file(KEY, inputfile, "redhat", progress=broken)
+
+    with pytest.raises(jce.CancelledError):
+        ks.sign_file(
+            KEY, inputfile, "redhat", progress=Progress(2), progress_interval=10000
+        )
@@ -0,0 +1,72 @@
+import os
+import tempfile
+import johnnycanencrypt as jce
+import pytest
+
+
+def setup_module(module):
+    module.tmpdirname = tempfile.TemporaryDirectory()
+
+
+def teardown_module(module):
+    del module.tmpdirname
+
+
+def test_create_newkey_revocation():
+    public, secret, fingerprint, revocation = jce.create_newkey(
+        "redhat", "test key <test@example.com>", "Cv25519"
+    )
+    assert revocation.startswith("-----BEGIN PGP SIGNATURE-----")
+    assert not jce.Cert(public.encode("utf-8")).details()["revoked"]
+    revoked = jce.apply_revocation(
+        jce.Cert(public.encode("utf-8")), revocation.encode("utf-8")
+    )
+    cert = jce.Cert(revoked.encode("utf-8"))
+    assert cert.fingerprint == fingerprint
+    assert not cert.is_secret
+    assert cert.details()["revoked"]
+
+
+def test_revoke_cert():
+    revocation = jce.revoke_cert(
+        "tests/files/secret.asc", "redhat", "compromised", "The laptop was stolen."
+    )
+    revoked = jce.apply_revocation("tests/files/public.asc", revocation.encode("utf-8"))
+    assert jce.Cert(revoked.encode("utf-8")).details()["revoked"]
+
+    # A secret key stays a secret key after merging
+    revoked = jce.apply_revocation("tests/files/secret.asc", revocation.encode("utf-8"))
+    assert jce.Cert(revoked.encode("utf-8")).is_secret
+
+
+def test_revoke_cert_errors():
+    with pytest.raises(jce.WrongPasswordError):
+        jce.revoke_cert("tests/files/secret.asc", "wrong password")
+    with pytest.raises(ValueError):
+        jce.revoke_cert("tests/files/secret.asc", "redhat", "lost")
+    with pytest.raises(jce.CryptoError):
+        jce.revoke_cert("tests/files/public.asc", "redhat")
+
+
+def test_apply_revocation_wrong_key():
+    revocation = jce.revoke_cert("tests/files/secret.asc", "redhat")
+    with pytest.raises(jce.CryptoError):
+        jce.apply_revocation("tests/files/hellopublic.asc", revocation.encode("utf-8"))
+
+
+def test_keystore_revocation():
+    ks = jce.KeyStore(tmpdirname.name)
+    key = ks.create_newkey("redhat", "test key <test@example.com>", "Cv25519")
+    revpath = os.path.join(tmpdirname.name, f"{key.fingerprint}.rev")
+    assert os.path.exists(revpath)
+    with open(revpath) as fobj:
+        revocation = fobj.read()
+    ks.apply_revocation(key.fingerprint, revocation)
+
+    # Both the public and the secret keys on disk are revoked now
+    for keytype in ["public", "secret"]:
+        k = ks.get_key(key.fingerprint, keytype)
+        assert jce.Cert.from_file(k.keypath).details()["revoked"]
+    # The store can still be loaded
+    ks = jce.KeyStore(tmpdirname.name)
+    assert (1, 1) == ks.details()
@@ -1,3 +1,4 @@
+import os
 import pytest
 import johnnycanencrypt as jce
 
@@ -27,5 +28,194 @@ def test_verify_bytes_must_fail():
 
 def test_sign_fail():
     j = jce.Johnny("tests/files/public.asc")
-    with pytest.raises(AttributeError):
+    with pytest.raises(jce.NoSigningKeyError):
         signature = j.sign_bytes_detached(DATA.encode("utf-8"), "redhat")
+
+
+def test_sign_verify_inline():
+    j = jce.Johnny("tests/files/secret.asc")
+    signed = j.sign_bytes(DATA.encode("utf-8"), "redhat", armor=True)
+    assert signed.startswith(b"-----BEGIN PGP MESSAGE-----")
+    jp = jce.Johnny("tests/files/public.asc")
+    content, verified = jp.verify_and_extract(signed)
+    assert content.decode("utf-8") == DATA
+    assert verified
+
+    # Signed by some other key
+    jh = jce.Johnny("tests/files/hellopublic.asc")
+    content, verified = jh.verify_and_extract(signed)
+    assert content.decode("utf-8") == DATA
+    assert not verified
+
+
+def test_verify_inline_malformed():
+    jp = jce.Johnny("tests/files/public.asc")
+    with pytest.raises(jce.MalformedMessageError):
+        jp.verify_and_extract(b"not a signed message")
+
+
+def test_sign_verify_inline_file(tmp_path):
+    inputfile = "tests/files/text.txt"
+    signed = str(tmp_path / "text.txt.gpg")
+    extracted = str(tmp_path / "text.txt")
+    j = jce.Johnny("tests/files/secret.asc")
+    assert j.sign_file(inputfile.encode("utf-8"), signed.encode("utf-8"), "redhat")
+    jp = jce.Johnny("tests/files/public.asc")
+    assert jp.verify_and_extract_file(signed.encode("utf-8"), extracted.encode("utf-8"))
+    with open(inputfile) as f1, open(extracted) as f2:
+        assert f1.read() == f2.read()
+
+    # Signed by some other key, the unauthenticated content is not written
+    other = str(tmp_path / "other.txt")
+    jh = jce.Johnny("tests/files/hellopublic.asc")
+    assert not jh.verify_and_extract_file(signed.encode("utf-8"), other.encode("utf-8"))
+    assert not os.path.exists(other)
+
+
+CLEARTEXT = """Release announcement
+- first item \t
+-----BEGIN PGP SIGNATURE----- is just text here
+From the team
+"""
+
+
+def test_sign_verify_cleartext():
+    j = jce.Johnny("tests/files/secret.asc")
+    signed = j.sign_cleartext(CLEARTEXT.encode("utf-8"), "redhat")
+    assert signed.startswith("-----BEGIN PGP SIGNED MESSAGE-----\nHash: SHA256\n\n")
+    # The lines starting with a dash are escaped
+    assert "\n- - first item\n" in signed
+    assert "\n- -----BEGIN PGP SIGNATURE----- is just text here\n" in signed
+
+    jp = jce.Johnny("tests/files/public.asc")
+    text, verified = jp.verify_cleartext(signed.encode("utf-8"))
+    assert verified
+    # The trailing whitespace is not part of the signed text
+    assert text.split("\n") == [
+        "Release announcement",
+        "- first item",
+        "-----BEGIN PGP SIGNATURE----- is just text here",
+        "From the team",
+    ]
+
+    # CRLF line endings do not change the signed text
+    _, verified = jp.verify_cleartext(signed.replace("\n", "\r\n").encode("utf-8"))
+    assert verified
+
+
+def test_verify_cleartext_must_fail():
+    j = jce.Johnny("tests/files/secret.asc")
+    signed = j.sign_cleartext(CLEARTEXT.encode("utf-8"), "redhat")
+    jp = jce.Johnny("tests/files/public.asc")
+    tampered = signed.replace("From the team", "From the other team")
+    _, verified = jp.verify_cleartext(tampered.encode("utf-8"))
+    assert not verified
+
+    jh = jce.Johnny("tests/files/hellopublic.asc")
+    _, verified = jh.verify_cleartext(signed.encode("utf-8"))
+    assert not verified
+
+
+def test_verify_cleartext_malformed():
+    j = jce.Johnny("tests/files/secret.asc")
+    signed = j.sign_cleartext(CLEARTEXT.encode("utf-8"), "redhat")
+    jp = jce.Johnny("tests/files/public.asc")
+    with pytest.raises(jce.MalformedMessageError):
+        wronghash = signed.replace("Hash: SHA256", "Hash: SHA1")
+        jp.verify_cleartext(wronghash.encode("utf-8"))
+    with pytest.raises(jce.MalformedMessageError):
+        jp.verify_cleartext(CLEARTEXT.encode("utf-8"))
+
+
+def test_verification_result():
+    j = jce.Johnny("tests/files/secret.asc")
+    signature = j.sign_bytes_detached(DATA.encode("utf-8"), "redhat")
+    jp = jce.Johnny("tests/files/public.asc")
+    result = jp.verify_bytes(DATA.encode("utf-8"), signature.encode("utf-8"))
+    assert result.valid
+    assert result.verified == ["BB2D3F20233286371C3123D5209940B9669ED621"]
+    (sig,) = result.signatures
+    assert sig["status"] == "good"
+    assert sig["good"]
+    assert sig["fingerprint"] == "BB2D3F20233286371C3123D5209940B9669ED621"
+    assert sig["signing_key"]
+    assert sig["keyid"]
+    assert sig["creation_time"]
+    assert sig["hash_algo"]
+    assert sig["error"] is None
+
+
+def test_verification_result_bad():
+    j = jce.Johnny("tests/files/secret.asc")
+    signature = j.sign_bytes_detached(DATA.encode("utf-8"), "redhat")
+    jp = jce.Johnny("tests/files/public.asc")
+    result = jp.verify_bytes(b"some other data", signature.encode("utf-8"))
+    assert not result
+    (sig,) = result.signatures
+    assert sig["status"] == "bad"
+    assert sig["fingerprint"] == "BB2D3F20233286371C3123D5209940B9669ED621"
+    assert sig["error"]
+    assert result.verified == []
+
+    # Verifying with some other key
+    jh = jce.Johnny("tests/files/hellopublic.asc")
+    result = jh.verify_bytes(DATA.encode("utf-8"), signature.encode("utf-8"))
+    assert not result.valid
+    (sig,) = result.signatures
+    assert sig["status"] == "unknown_key"
+    assert sig["fingerprint"] is None
+
+
+def test_verification_result_revoked_key():
+    revocation = jce.revoke_cert("tests/files/secret.asc", "redhat", "compromised")
+    revoked = jce.apply_revocation("tests/files/public.asc", revocation.encode("utf-8"))
+    j = jce.Johnny("tests/files/secret.asc")
+    signature = j.sign_bytes_detached(DATA.encode("utf-8"), "redhat")
+    jp = jce.Johnny(jce.Cert(revoked.encode("utf-8")))
+    result = jp.verify_bytes(DATA.encode("utf-8"), signature.encode("utf-8"))
+    assert not result
+    assert result.signatures[0]["status"] == "revoked_key"
+
+
+def test_verify_malformed_signature():
+    jp = jce.Johnny("tests/files/public.asc")
+    with pytest.raises(jce.MalformedMessageError):
+        jp.verify_bytes(DATA.encode("utf-8"), b"not a signature")
+
+
+def test_verify_multiple_signers():
+    signature = jce.Johnny("tests/files/secret.asc").sign_bytes_detached(
+        DATA.encode("utf-8"), "redhat"
+    )
+    signature2 = jce.Johnny("tests/files/hellosecret.asc").sign_bytes_detached(
+        DATA.encode("utf-8"), "redhat"
+    )
+    keys = ["tests/files/hellopublic.asc", "tests/files/public.asc"]
+    result = jce.verify_bytes_detached(
+        keys, DATA.encode("utf-8"), signature.encode("utf-8")
+    )
+    assert result
+    assert result.verified == ["BB2D3F20233286371C3123D5209940B9669ED621"]
+    assert result.has_good_signers(1)
+    assert not result.has_good_signers(2)
+
+    # Both the signatures together
+    both = signature.encode("utf-8") + signature2.encode("utf-8")
+    result = jce.verify_bytes_detached(keys, DATA.encode("utf-8"), both)
+    assert len(result.signatures) == 2
+    assert sorted(result.verified) == [
+        "6AC6957E2589CB8B5221F6508ADA07F0A0F7BA99",
+        "BB2D3F20233286371C3123D5209940B9669ED621",
+    ]
+    assert result.has_good_signers(2)
+
+    # Only one of the signers is known
+    result = jce.verify_bytes_detached(
+        ["tests/files/public.asc"], DATA.encode("utf-8"), both
+    )
+    assert result.has_good_signers(1)
+    assert not result.has_good_signers(2)
+    assert sorted(sig["status"] for sig in result.signatures) == [
+        "good",
+        "unknown_key",
+    ]
@@ -0,0 +1,132 @@
+import io
+import os
+import tempfile
+
+import pytest
+import johnnycanencrypt as jce
+
+DATA = "Kushal loves 🦀"
+
+
+def setup_module(module):
+    module.tmpdirname = tempfile.TemporaryDirectory()
+
+
+def teardown_module(module):
+    del module.tmpdirname
+
+
+class ChunkedReader:
+    "Returns at most 10 bytes for each read, like a slow socket"
+
+    def __init__(self, data):
+        self.data = io.BytesIO(data)
+
+    def read(self, size=-1):
+        return self.data.read(min(size, 10))
+
+
+class BrokenReader:
+    def read(self, size=-1):
+        raise ConnectionResetError("The upload was interrupted.")
+
+
+def test_encrypt_decrypt_stream():
+    data = DATA.encode("utf-8") * 1000
+    encrypted = io.BytesIO()
+    assert jce.encrypt_stream(
+        ["tests/files/public.asc"], io.BytesIO(data), encrypted, armor=True
+    )
+    assert encrypted.getvalue().startswith(b"-----BEGIN PGP MESSAGE-----\n")
+    encrypted.seek(0)
+    decrypted = io.BytesIO()
+    result = jce.decrypt_stream(
+        ["tests/files/secret.asc"], encrypted, decrypted, "redhat"
+    )
+    assert data == decrypted.getvalue()
+    assert result.plaintext is None
+    assert result.fingerprint == "9EBFCA465490663C22AEF1443CE170115CF4322E"
+    assert result.format
+
+
+def test_encrypt_decrypt_stream_chunked():
+    data = DATA.encode("utf-8") * 100
+    encrypted = io.BytesIO()
+    assert jce.encrypt_stream(
+        ["tests/files/public.asc"], ChunkedReader(data), encrypted, compression="zlib"
+    )
+    decrypted = io.BytesIO()
+    jce.decrypt_stream(
+        ["tests/files/secret.asc"],
+        ChunkedReader(encrypted.getvalue()),
+        decrypted,
+        "redhat",
+    )
+    assert data == decrypted.getvalue()
+
+
+def test_encrypt_decrypt_stream_files():
+    inputfile = "tests/files/text.txt"
+    encrypted_path = os.path.join(tmpdirname.name, "text.txt.pgp")
+    decrypted_path = os.path.join(tmpdirname.name, "text.txt")
+    ks = jce.KeyStore("tests/files/store")
+    key = "BB2D3F20233286371C3123D5209940B9669ED621"
+    with open(inputfile, "rb") as fin, open(encrypted_path, "wb") as fout:
+        assert ks.encrypt_stream(key, fin, fout)
+    with open(encrypted_path, "rb") as fin, open(decrypted_path, "wb") as fout:
+        result = ks.decrypt_stream(None, fin, fout, password="redhat")
+    assert result.fingerprint == "9EBFCA465490663C22AEF1443CE170115CF4322E"
+    with open(inputfile, "rb") as f1, open(decrypted_path, "rb") as f2:
+        assert f1.read() == f2.read()
+
+
+def test_stream_errors():
+    with pytest.raises(ConnectionResetError):
+        jce.encrypt_stream(["tests/files/public.asc"], BrokenReader(), io.BytesIO())
+    with pytest.raises(ConnectionResetError):
+        jce.decrypt_stream(
+            ["tests/files/secret.asc"], BrokenReader(), io.BytesIO(), "redhat"
+        )
+    # The input must be opened in binary mode
+    with pytest.raises(TypeError):
+        jce.encrypt_stream(
+            ["tests/files/public.asc"], io.StringIO(DATA), io.BytesIO()
+        )
+
+
+def chunks(data, size=100):
+    return [data[i : i + size] for i in range(0, len(data), size)]
+
+
+def test_encryption_decryption_stream():
+    data = DATA.encode("utf-8") * 1000
+    stream = jce.EncryptionStream(["tests/files/public.asc"], armor=True)
+    encrypted = b"".join(stream.write(chunk) for chunk in chunks(data))
+    encrypted += stream.finish()
+    assert encrypted.startswith(b"-----BEGIN PGP MESSAGE-----\n")
+    assert data == jce.decrypt_bytes_with_keys(
+        ["tests/files/secret.asc"], encrypted, "redhat"
+    ).plaintext
+
+    stream = jce.DecryptionStream(["tests/files/secret.asc"], "redhat")
+    decrypted = b"".join(stream.write(chunk) for chunk in chunks(encrypted, 10))
+    result = stream.finish()
+    assert data == decrypted + result.plaintext
+    assert result.fingerprint == "9EBFCA465490663C22AEF1443CE170115CF4322E"
+
+
+def test_stream_classes_errors():
+    stream = jce.EncryptionStream(["tests/files/public.asc"])
+    stream.write(DATA.encode("utf-8"))
+    encrypted = stream.finish()
+    with pytest.raises(ValueError):
+        stream.write(b"more data")
+    with pytest.raises(ValueError):
+        stream.finish()
+    with pytest.raises(ValueError):
+        jce.EncryptionStream(["tests/files/public.asc"], cipher="rot13")
+
+    with pytest.raises(jce.WrongPasswordError):
+        stream = jce.DecryptionStream(["tests/files/secret.asc"], "wrongpassword")
+        stream.write(encrypted)
+        stream.finish()
@@ -0,0 +1,138 @@
+import time
+import tempfile
+import johnnycanencrypt as jce
+import pytest
+
+DATA = "Kushal loves 🦀"
+ENCRYPTION_SUBKEY = "9EBFCA465490663C22AEF1443CE170115CF4322E"
+
+
+def setup_module(module):
+    module.tmpdirname = tempfile.TemporaryDirectory()
+
+
+def teardown_module(module):
+    del module.tmpdirname
+
+
+def subkeys_of(armored):
+    "Returns the subkeys from the details of the armored key"
+    return {
+        sub["fingerprint"]: sub
+        for sub in jce.Cert(armored.encode("utf-8")).details()["subkeys"]
+    }
+
+
+def test_add_subkey():
+    old = subkeys_of(open("tests/files/public.asc").read())
+    public, secret = jce.add_subkey(
+        "tests/files/secret.asc", "redhat", "Cv25519", expiration=3600
+    )
+    subkeys = subkeys_of(public)
+    assert len(subkeys) == len(old) + 1
+    (new,) = [sub for fp, sub in subkeys.items() if fp not in old]
+    assert new["valid"]
+    assert new["encrypt_storage"]
+    assert not new["sign"]
+    assert new["algorithm"] == "ECDH"
+    assert new["expiration_time"] == new["creation_time"] + 3600
+    assert subkeys_of(secret)[new["fingerprint"]]["secret_encrypted"]
+
+    # The data encrypted for the updated key can be decrypted with the new subkey
+    j = jce.Johnny(jce.Cert(secret.encode("utf-8")))
+    enc = j.encrypt_bytes(DATA.encode("utf-8"))
+    result = j.decrypt_bytes(enc, "redhat")
+    assert DATA == result.plaintext.decode("utf-8")
+
+
+def test_add_signing_subkey():
+    old = subkeys_of(open("tests/files/public.asc").read())
+    public, _ = jce.add_subkey(
+        "tests/files/secret.asc", "redhat", "Cv25519", signing=True
+    )
+    (new,) = [sub for fp, sub in subkeys_of(public).items() if fp not in old]
+    assert new["sign"]
+    assert new["algorithm"] == "EdDSA"
+    # A signing subkey is not used for encryption by default
+    assert not new["encrypt_storage"]
+    assert not new["encrypt_transport"]
+    with pytest.raises(ValueError):
+        jce.add_subkey("tests/files/secret.asc", "redhat", encryption="none")
+    with pytest.raises(ValueError):
+        jce.add_subkey(
+            "tests/files/secret.asc",
+            "redhat",
+            "Cv25519",
+            signing=True,
+            encryption="storage",
+        )
+
+
+def test_encrypt_to_transport_subkey():
+    "A key with only a transport encryption subkey can still be encrypted to"
+    public, secret, _, _ = jce.create_newkey(
+        "redhat", "test key <test@example.com>", "Cv25519", encryption="transport"
+    )
+    subkeys = subkeys_of(public).values()
+    assert not any(sub["encrypt_storage"] for sub in subkeys)
+    assert any(sub["encrypt_transport"] for sub in subkeys)
+
+    enc = jce.encrypt_bytes_to_bytes(
+        [jce.Cert(public.encode("utf-8"))], DATA.encode("utf-8")
+    )
+    result = jce.Johnny(jce.Cert(secret.encode("utf-8"))).decrypt_bytes(enc, "redhat")
+    assert DATA == result.plaintext.decode("utf-8")
+
+
+def test_revoke_subkey():
+    public, secret = jce.add_subkey("tests/files/secret.asc", "redhat", "Cv25519")
+    public, secret = jce.revoke_subkey(
+        jce.Cert(secret.encode("utf-8")), "redhat", ENCRYPTION_SUBKEY, "superseded"
+    )
+    assert subkeys_of(public)[ENCRYPTION_SUBKEY]["revoked"]
+
+    # Only the new subkey is used for encryption now
+    enc = jce.encrypt_bytes_to_bytes(
+        [jce.Cert(public.encode("utf-8"))], DATA.encode("utf-8")
+    )
+    result = jce.Johnny(jce.Cert(secret.encode("utf-8"))).decrypt_bytes(enc, "redhat")
+    assert "3CE170115CF4322E" not in result.recipients
+    assert len(result.recipients) == 1
+
+
+def test_set_subkey_expiration():
+    when = int(time.time()) + 3600
+    public, secret = jce.set_subkey_expiration(
+        "tests/files/secret.asc", "redhat", ENCRYPTION_SUBKEY, when
+    )
+    assert subkeys_of(public)[ENCRYPTION_SUBKEY]["expiration_time"] == when
+    # Now remove the expiration
+    public, _ = jce.set_subkey_expiration(
+        jce.Cert(secret.encode("utf-8")), "redhat", ENCRYPTION_SUBKEY, None
+    )
+    assert subkeys_of(public)[ENCRYPTION_SUBKEY]["expiration_time"] is None
+
+
+def test_subkey_errors():
+    with pytest.raises(ValueError):
+        jce.revoke_subkey("tests/files/secret.asc", "redhat", "AAAA")
+    with pytest.raises(ValueError):
+        jce.set_subkey_expiration(
+            "tests/files/secret.asc", "redhat", ENCRYPTION_SUBKEY, 0
+        )
+    with pytest.raises(jce.WrongPasswordError):
+        jce.add_subkey("tests/files/secret.asc", "wrong password")
+
+
+def test_keystore_subkeys():
+    ks = jce.KeyStore(tmpdirname.name)
+    key = ks.create_newkey("redhat", "test key <test@example.com>", "Cv25519")
+    old = subkeys_of(open(key.keypath).read())
+    key = ks.add_subkey(key, "redhat", "Cv25519")
+    (new,) = [fp for fp in subkeys_of(open(key.keypath).read()) if fp not in old]
+    for fingerprint, sub in old.items():
+        if sub["encrypt_storage"]:
+            ks.revoke_subkey(key, fingerprint, "redhat", "superseded")
+    encrypted = ks.encrypt(key, DATA)
+    assert DATA == ks.decrypt(key.fingerprint, encrypted, "redhat").decode("utf-8")
+    assert subkeys_of(open(key.keypath).read())[new]["valid"]
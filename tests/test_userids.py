@@ -0,0 +1,86 @@
+import tempfile
+import johnnycanencrypt as jce
+import pytest
+
+
+def setup_module(module):
+    module.tmpdirname = tempfile.TemporaryDirectory()
+
+
+def teardown_module(module):
+    del module.tmpdirname
+
+
+def uids_of(armored):
+    "Returns the uids from the details of the armored key"
+    return {uid["value"]: uid for uid in jce.Cert(armored.encode("utf-8")).details()["uids"]}
+
+
+def test_add_userid():
+    public, secret = jce.add_userid(
+        "tests/files/secret.asc", "redhat", "test key <test@example.org>"
+    )
+    assert not jce.Cert(public.encode("utf-8")).is_secret
+    assert jce.Cert(secret.encode("utf-8")).is_secret
+    uids = uids_of(public)
+    assert len(uids) == 2
+    newuid = uids["test key <test@example.org>"]
+    assert newuid["valid"]
+    assert not newuid["primary"]
+    assert newuid["email"] == "test@example.org"
+
+
+def test_set_primary_userid():
+    _, secret = jce.add_userid(
+        "tests/files/secret.asc", "redhat", "test key <test@example.org>"
+    )
+    public, secret = jce.set_primary_userid(
+        jce.Cert(secret.encode("utf-8")), "redhat", "test key <test@example.org>"
+    )
+    primary = [uid for uid in uids_of(public).values() if uid["primary"]]
+    assert len(primary) == 1
+    assert primary[0]["email"] == "test@example.org"
+    # The key can still be used to certify and encrypt
+    details = jce.Cert(public.encode("utf-8")).details()
+    assert details["certify"]
+    assert any(sub["encrypt_storage"] for sub in details["subkeys"])
+
+
+def test_revoke_userid():
+    _, secret = jce.add_userid(
+        "tests/files/secret.asc", "redhat", "test key <test@example.org>"
+    )
+    public, _ = jce.revoke_userid(
+        jce.Cert(secret.encode("utf-8")),
+        "redhat",
+        "test key <test@example.org>",
+        "retired",
+        "Moved to a new job.",
+    )
+    uids = uids_of(public)
+    assert uids["test key <test@example.org>"]["revoked"]
+    assert not uids["test key <test@example.org>"]["primary"]
+
+
+def test_userid_errors():
+    with pytest.raises(ValueError):
+        jce.revoke_userid("tests/files/secret.asc", "redhat", "nosuchuid")
+    with pytest.raises(ValueError):
+        jce.set_primary_userid("tests/files/secret.asc", "redhat", "nosuchuid")
+    with pytest.raises(jce.WrongPasswordError):
+        jce.add_userid("tests/files/secret.asc", "wrong password", "new uid")
+
+
+def test_keystore_userids():
+    ks = jce.KeyStore(tmpdirname.name)
+    key = ks.create_newkey("redhat", "test key <test@example.com>", "Cv25519")
+    key = ks.add_userid(key, "test key <test@example.org>", "redhat")
+    assert key.keytype == "public"
+    # The new uid can be searched in the store
+    assert ks.get_keys(email="test@example.org") == [key]
+    assert ks.get_keys(email="test@example.com") == [key]
+
+    ks.set_primary_userid(key.fingerprint, "test key <test@example.org>", "redhat")
+    cert = jce.Cert.from_file(ks.get_key(key.fingerprint, "secret").keypath)
+    primary = [uid for uid in cert.details()["uids"] if uid["primary"]]
+    assert primary[0]["email"] == "test@example.org"